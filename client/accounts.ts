@@ -9,6 +9,7 @@ export const raffle_program = new PublicKey("H7gQ6ueKQbPMYLnLyxFYxr1hFFLKHDyyMD9
 export const rng_program = new PublicKey("FEED1qspts3SRuoEyG29NMNpsTKX8yG9NGMinNC4GeYB");
 export const entropy_account = new PublicKey("CTyyJKQHo6JhtVYBaXcota9NozebV3vHF872S8ag2TUS");
 export const rng_program_fee_account = new PublicKey("WjtcArL5m5peH8ZmAdTtyFF9qjyNxjQ2qp4Gz1YEQdy");
+export const metadata_program = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
 
 export const token_mint = new PublicKey("4pnJLwuTL4cn5soLhnGF1YqbCEAWJUmUKwJnNX9PuaMH");
 
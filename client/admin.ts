@@ -16,10 +16,10 @@
   } from "@solana/web3.js";
 
   import * as borsh from "borsh";
-  import { Counter, CounterSchema, InitRaffle, InitRaffleSchema, RewardFeeType, RewardFeeTypeSchema, RSchema  } from "./models";
+  import { Counter, CounterSchema, InitRaffle, InitRaffleSchema, Raffle, RaffleSchema, RaffleExtensionSchema, RaffleSeries, RaffleSeriesSchema, InitSeriesSchema, RewardFeeType, RewardFeeTypeSchema, RSchema, FeeCurrencySchema  } from "./models";
   import { connection} from './connection';
   import { raffle_program, } from "./accounts"
-  import { ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+  import { ASSOCIATED_TOKEN_PROGRAM_ID, createTransferCheckedInstruction, getAssociatedTokenAddressSync, TOKEN_PROGRAM_ID } from "@solana/spl-token";
   import { numberToLEBytes8, stringToNumberArray32Bytes } from "./utils";
   import { get_token_program_and_decimals } from "./get_info";
 
@@ -33,7 +33,7 @@
     is_unlimited_participant_allowed:number,
     raffle_name_str:string,
     fee:number,
-    participants_required:bigint,
+    max_participants:bigint,
     raffle_time:bigint,
     multiple_participation_allowed:number,
     participation_fee_type:bigint,
@@ -44,7 +44,32 @@
     requirement_token_mint:PublicKey,
     winner_count:bigint,
     is_increasing_pool:number,
-    transfer_fee_to_pool:bigint[]
+    transfer_fee_to_pool:bigint[],
+    referral_bps:bigint = BigInt(0),
+    payees:PublicKey[] = [],
+    payee_shares_bps:bigint[] = [],
+    allowlist_root:number[] = Array(32).fill(0),
+    one_entry_per_nft:number = 0,
+    recheck_requirement_at_claim:number = 0,
+    max_entries_per_wallet:bigint = BigInt(0),
+    min_participants:bigint = BigInt(0),
+    refund_below_min:number = 0,
+    start_time:bigint = BigInt(0),
+    rollover:number = 0,
+    claim_period:bigint = BigInt(0),
+    pool_bps:bigint = BigInt(0),
+    pool_weights_bps:bigint[] = [],
+    prize_shares_bps:bigint[] = [],
+    consolation_type:number = 0,
+    consolation_amount:bigint = BigInt(0),
+    consolation_funding:bigint = BigInt(0),
+    pricing_mode:number = 0,
+    price_step_times:bigint[] = [],
+    price_step_prices:bigint[] = [],
+    end_price:bigint = BigInt(0),
+    attester:PublicKey = PublicKey.default,
+    accepted_fee_types:bigint[] = [],
+    accepted_fee_prices:bigint[] = [],
     ) => {
 
 
@@ -121,7 +146,7 @@
         is_unlimited_participant_allowed,
         raffle_name,
         participation_fee,
-        participants_required,
+        max_participants,
         raffle_time,
         multiple_participation_allowed,
         participation_fee_type,
@@ -133,7 +158,32 @@
         required_token_decimals,
         winner_count,
         is_increasing_pool,
-        transfer_fee_to_pool
+        transfer_fee_to_pool,
+        referral_bps,
+        payees: payees.map((payee) => Array.from(payee.toBytes())),
+        payee_shares_bps,
+        allowlist_root,
+        one_entry_per_nft,
+        recheck_requirement_at_claim,
+        max_entries_per_wallet,
+        min_participants,
+        refund_below_min,
+        start_time,
+        rollover,
+        claim_period,
+        pool_bps,
+        pool_weights_bps,
+        prize_shares_bps,
+        consolation_type,
+        consolation_amount,
+        consolation_funding,
+        pricing_mode,
+        price_step_times,
+        price_step_prices,
+        end_price,
+        attester: Array.from(attester.toBytes()),
+        accepted_fee_types,
+        accepted_fee_prices,
        };
 
 
@@ -177,6 +227,7 @@
     const term_account = PublicKey.findProgramAddressSync([Buffer.from("term")], raffle_program)[0];
     const reward_type_pda = PublicKey.findProgramAddressSync([Buffer.from("rewtype"),reward_type_no_serialized], raffle_program)[0];
     const fee_type_pda = PublicKey.findProgramAddressSync([Buffer.from("feetype"),fee_type_no_serialized], raffle_program)[0];
+    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
 
 
      const keys:AccountMeta[] = [];
@@ -195,6 +246,7 @@
      const fee_mint_meta:AccountMeta = { isSigner: false, isWritable: false, pubkey: fee_mint };
      const fee_mint_token_program_meta:AccountMeta = { isSigner: false, isWritable: false, pubkey: TOKEN_PROGRAM_ID };
      const SYSVAR_RENT_PUBKEY_meta:AccountMeta = { isSigner: false, isWritable: false, pubkey: SYSVAR_RENT_PUBKEY };
+     const config_account_meta:AccountMeta = { isSigner: false, isWritable: false, pubkey: config_account };
      const SystemProgram_meta:AccountMeta = { isSigner: false, isWritable: false, pubkey: SystemProgram.programId };
      const ASSOCIATED_TOKEN_PROGRAM_ID_meta:AccountMeta = { isSigner: false, isWritable: false, pubkey: ASSOCIATED_TOKEN_PROGRAM_ID };
      
@@ -213,6 +265,21 @@
      keys.push(fee_mint_meta)
      keys.push(fee_mint_token_program_meta)
      keys.push(SYSVAR_RENT_PUBKEY_meta)
+     keys.push(config_account_meta)
+
+     //accepted currencies follow the fixed accounts, token ones also pass the mint, the raffle ata and the token program
+     for (const accepted_fee_type of accepted_fee_types){
+      const [accepted_type_pda, accepted_mint] = await get_type_mint("feetype", accepted_fee_type);
+      keys.push({isSigner: false, isWritable: false, pubkey: accepted_type_pda})
+
+      if(accepted_fee_type != BigInt(1)){
+        const [accepted_token_program] = await get_token_program_and_decimals(accepted_mint);
+        const raffle_accepted_ata = getAssociatedTokenAddressSync(accepted_mint,raffle_pda,true,accepted_token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+        keys.push({isSigner: false, isWritable: false, pubkey: accepted_mint})
+        keys.push({isSigner: false, isWritable: true, pubkey: raffle_accepted_ata})
+        keys.push({isSigner: false, isWritable: false, pubkey: accepted_token_program})
+      }
+     }
 
 
 
@@ -232,6 +299,11 @@
       keys.push(raffle_token_program_meta)
      }
 
+     if(consolation_funding != BigInt(0) && participation_fee_type != BigInt(1)){
+      const initializer_fee_ata = getAssociatedTokenAddressSync(fee_mint,initializer.publicKey,false,TOKEN_PROGRAM_ID!,ASSOCIATED_TOKEN_PROGRAM_ID)
+      keys.push({isSigner: false, isWritable: true, pubkey: initializer_fee_ata})
+     }
+
      keys.push(SystemProgram_meta)
      keys.push(ASSOCIATED_TOKEN_PROGRAM_ID_meta)
 
@@ -260,6 +332,14 @@
   return delay(600)
 }
 
+  //each currency is collected separately, 0 is participation_fee_mint and n is accepted_fees[n-1]
+  const fee_currency_of = (raffle:Raffle, mint:PublicKey) => {
+    if (new PublicKey(raffle.participation_fee_mint).equals(mint)) {return 0}
+    const position = raffle.accepted_fees.findIndex((accepted) => new PublicKey(accepted.mint).equals(mint));
+    if (position < 0) {throw new Error("mint is not accepted by this raffle")}
+    return position + 1
+  }
+
   export const collect_fee_initializer = async (raffle_no:bigint, initializer:Keypair) => {
 
 
@@ -268,21 +348,33 @@
     const raffle_pda = PublicKey.findProgramAddressSync([Buffer.from("raffle"),le_bytes],raffle_program)[0]
 
 
-    const term_account = PublicKey.findProgramAddressSync([Buffer.from("term")], raffle_program)[0];
-
     const fee_collector_account = PublicKey.findProgramAddressSync([Buffer.from("fee_collector")], raffle_program)[0];
+    const fee_ledger = PublicKey.findProgramAddressSync([Buffer.from("fee_ledger"),SystemProgram.programId.toBytes()], raffle_program)[0];
 
     
 
-    const ix = new TransactionInstruction({
-      programId: raffle_program,
-      keys: [
+    const raffle_account_info = await connection.getAccountInfo(raffle_pda);
+    const raffle = borsh.deserialize(RaffleSchema,raffle_account_info?.data!) as Raffle;
+
+    const fee_currency = fee_currency_of(raffle, SystemProgram.programId);
+
+    const keys:AccountMeta[] = [
         { isSigner: true, isWritable: true, pubkey: initializer.publicKey },
         { isSigner: false, isWritable: true, pubkey: raffle_pda },
-        { isSigner: false, isWritable: true, pubkey: term_account },
         { isSigner: false, isWritable: true, pubkey: fee_collector_account },
-   ],
-      data: Buffer.from([200])
+        { isSigner: false, isWritable: true, pubkey: fee_ledger },
+    ];
+
+    for (const payee of raffle.payees) {
+      keys.push({ isSigner: false, isWritable: true, pubkey: new PublicKey(payee) })
+    }
+
+    keys.push({ isSigner: false, isWritable: false, pubkey: SystemProgram.programId })
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys,
+      data: Buffer.from(Uint8Array.of(200, ...borsh.serialize(FeeCurrencySchema, { fee_currency })))
     });
 
     const message = new TransactionMessage({
@@ -300,18 +392,325 @@
   return delay(600)
 }
 
-  export const collect_fee_token_initializer = async (raffle_no:bigint, initializer:Keypair, mint:PublicKey) => {
-
+  export const extend_raffle = async (raffle_no:bigint, initializer:Keypair, new_raffle_time:bigint) => {
 
     const le_bytes = numberToLEBytes8(raffle_no)
 
     const raffle_pda = PublicKey.findProgramAddressSync([Buffer.from("raffle"),le_bytes],raffle_program)[0]
 
+    const encoded = borsh.serialize(RaffleExtensionSchema, { new_raffle_time });
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys: [
+        { isSigner: true, isWritable: false, pubkey: initializer.publicKey },
+        { isSigner: false, isWritable: true, pubkey: raffle_pda },
+      ],
+      data: Buffer.from(Uint8Array.of(230, ...encoded))
+    });
+
+    const message = new TransactionMessage({
+      instructions: [ix],
+      payerKey: initializer.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash
+    }).compileToV0Message();
+
+    const tx = new VersionedTransaction(message);
+    tx.sign([initializer]);
+
+    await connection.sendTransaction(tx);
+
+    return delay(600)
+}
+
+  const series_address = (creator:PublicKey, series_id:bigint) => {
+    return PublicKey.findProgramAddressSync([Buffer.from("series"),creator.toBytes(),numberToLEBytes8(series_id)],raffle_program)[0]
+  }
+
+  const get_type_mint = async (seed:string, no:bigint) => {
+    const type_pda = PublicKey.findProgramAddressSync([Buffer.from(seed),numberToLEBytes8(no)],raffle_program)[0];
+    const type_info = await connection.getAccountInfo(type_pda);
+    const type = borsh.deserialize(RewardFeeTypeSchema,type_info?.data!) as RewardFeeType;
+    return [type_pda, new PublicKey(type.mint)] as [PublicKey,PublicKey];
+  }
+
+  //template amounts are in raw units, raffle_time and start_time are ignored
+  export const init_series = async (creator:Keypair, series_id:bigint, duration:bigint, template:InitRaffle) => {
+
+    const series_pda = series_address(creator.publicKey, series_id);
+
+    const [reward_type_pda, reward_mint] = await get_type_mint("rewtype", template.reward_type);
+    const [fee_type_pda] = await get_type_mint("feetype", template.participation_fee_type);
 
     const term_account = PublicKey.findProgramAddressSync([Buffer.from("term")], raffle_program)[0];
+    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
+
+    const encoded = borsh.serialize(InitSeriesSchema, { series_id, duration, template });
+
+    const keys:AccountMeta[] = [
+      { isSigner: true, isWritable: true, pubkey: creator.publicKey },
+      { isSigner: false, isWritable: true, pubkey: series_pda },
+      { isSigner: false, isWritable: false, pubkey: term_account },
+      { isSigner: false, isWritable: false, pubkey: reward_type_pda },
+      { isSigner: false, isWritable: false, pubkey: fee_type_pda },
+      { isSigner: false, isWritable: false, pubkey: config_account },
+    ];
+
+    if(template.reward_type != BigInt(1)){
+      const [reward_token_program] = await get_token_program_and_decimals(reward_mint);
+      const series_reward_ata = getAssociatedTokenAddressSync(reward_mint,series_pda,true,reward_token_program,ASSOCIATED_TOKEN_PROGRAM_ID);
+
+      keys.push({ isSigner: false, isWritable: false, pubkey: reward_mint })
+      keys.push({ isSigner: false, isWritable: true, pubkey: series_reward_ata })
+      keys.push({ isSigner: false, isWritable: false, pubkey: reward_token_program })
+      keys.push({ isSigner: false, isWritable: false, pubkey: SYSVAR_RENT_PUBKEY })
+      keys.push({ isSigner: false, isWritable: false, pubkey: SystemProgram.programId })
+      keys.push({ isSigner: false, isWritable: false, pubkey: ASSOCIATED_TOKEN_PROGRAM_ID })
+    }else{
+      keys.push({ isSigner: false, isWritable: false, pubkey: SystemProgram.programId })
+    }
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys,
+      data: Buffer.from(Uint8Array.of(240, ...encoded))
+    });
+
+    const message = new TransactionMessage({
+      instructions: [ix],
+      payerKey: creator.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash
+    }).compileToV0Message();
+
+    const tx = new VersionedTransaction(message);
+    tx.sign([creator]);
+
+    await connection.sendTransaction(tx);
+
+    return delay(600)
+}
+
+  //rounds are paid from the series escrow, anyone can top it up with a plain transfer
+  export const fund_series = async (funder:Keypair, creator:PublicKey, series_id:bigint, amount:bigint) => {
+
+    const series_pda = series_address(creator, series_id);
+
+    const series_info = await connection.getAccountInfo(series_pda);
+    const series = borsh.deserialize(RaffleSeriesSchema,series_info?.data!) as RaffleSeries;
+
+    let ix:TransactionInstruction;
+
+    if(series.template.reward_type == BigInt(1)){
+      ix = SystemProgram.transfer({ fromPubkey: funder.publicKey, toPubkey: series_pda, lamports: amount });
+    }else{
+      const [, reward_mint] = await get_type_mint("rewtype", series.template.reward_type);
+      const [reward_token_program, decimals] = await get_token_program_and_decimals(reward_mint);
+      const funder_ata = getAssociatedTokenAddressSync(reward_mint,funder.publicKey,false,reward_token_program,ASSOCIATED_TOKEN_PROGRAM_ID);
+      const series_reward_ata = getAssociatedTokenAddressSync(reward_mint,series_pda,true,reward_token_program,ASSOCIATED_TOKEN_PROGRAM_ID);
+      ix = createTransferCheckedInstruction(funder_ata,reward_mint,series_reward_ata,funder.publicKey,amount,decimals,[],reward_token_program);
+    }
+
+    const message = new TransactionMessage({
+      instructions: [ix],
+      payerKey: funder.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash
+    }).compileToV0Message();
+
+    const tx = new VersionedTransaction(message);
+    tx.sign([funder]);
+
+    await connection.sendTransaction(tx);
+
+    return delay(600)
+}
+
+  //permissionless, the payer covers the rent of the new round's accounts
+  export const spawn_series_round = async (payer:Keypair, creator:PublicKey, series_id:bigint) => {
+
+    const series_pda = series_address(creator, series_id);
+
+    const series_info = await connection.getAccountInfo(series_pda);
+    const series = borsh.deserialize(RaffleSeriesSchema,series_info?.data!) as RaffleSeries;
+    const template = series.template;
+
+    const counter_account = PublicKey.findProgramAddressSync([Buffer.from("counter")],raffle_program)[0]
+    const counter_info = await connection.getAccountInfo(counter_account);
+    const counter = borsh.deserialize(CounterSchema,counter_info?.data!) as Counter;
+
+    const raffle_no = BigInt(Number(counter.number_of_raffles) + 1);
+    const raffle_pda = PublicKey.findProgramAddressSync([Buffer.from("raffle"),numberToLEBytes8(raffle_no)],raffle_program)[0]
+
+    const [reward_type_pda, reward_mint] = await get_type_mint("rewtype", template.reward_type);
+    const [fee_type_pda, fee_mint] = await get_type_mint("feetype", template.participation_fee_type);
+
+    const reward_token_program = template.reward_type == BigInt(1) ? TOKEN_PROGRAM_ID : (await get_token_program_and_decimals(reward_mint))[0];
+    const fee_token_program = template.participation_fee_type == BigInt(1) ? TOKEN_PROGRAM_ID : (await get_token_program_and_decimals(fee_mint))[0];
+
+    const raffle_reward_ata = getAssociatedTokenAddressSync(reward_mint,raffle_pda,true,reward_token_program,ASSOCIATED_TOKEN_PROGRAM_ID);
+    const raffle_fee_ata = getAssociatedTokenAddressSync(fee_mint,raffle_pda,true,fee_token_program,ASSOCIATED_TOKEN_PROGRAM_ID);
+    const series_reward_ata = getAssociatedTokenAddressSync(reward_mint,series_pda,true,reward_token_program,ASSOCIATED_TOKEN_PROGRAM_ID);
+
+    const term_account = PublicKey.findProgramAddressSync([Buffer.from("term")], raffle_program)[0];
+    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
+
+    const keys:AccountMeta[] = [
+      { isSigner: true, isWritable: true, pubkey: payer.publicKey },
+      { isSigner: false, isWritable: true, pubkey: series_pda },
+      { isSigner: false, isWritable: true, pubkey: raffle_pda },
+      { isSigner: false, isWritable: true, pubkey: raffle_reward_ata },
+      { isSigner: false, isWritable: true, pubkey: raffle_fee_ata },
+      { isSigner: false, isWritable: true, pubkey: series_reward_ata },
+      { isSigner: false, isWritable: true, pubkey: counter_account },
+      { isSigner: false, isWritable: false, pubkey: term_account },
+      { isSigner: false, isWritable: false, pubkey: reward_type_pda },
+      { isSigner: false, isWritable: false, pubkey: fee_type_pda },
+      { isSigner: false, isWritable: false, pubkey: reward_mint },
+      { isSigner: false, isWritable: false, pubkey: reward_token_program },
+      { isSigner: false, isWritable: false, pubkey: fee_mint },
+      { isSigner: false, isWritable: false, pubkey: fee_token_program },
+      { isSigner: false, isWritable: false, pubkey: SYSVAR_RENT_PUBKEY },
+      { isSigner: false, isWritable: false, pubkey: config_account },
+    ];
+
+    if(series.round_no > BigInt(0)){
+      const previous_raffle = PublicKey.findProgramAddressSync([Buffer.from("raffle"),numberToLEBytes8(series.current_raffle_no)],raffle_program)[0]
+      keys.push({ isSigner: false, isWritable: false, pubkey: previous_raffle })
+    }
+
+    if(template.requirement_to_participate == 1){
+      const requirement_mint = new PublicKey(template.requirement_mint);
+      const [required_mint_token_program] = await get_token_program_and_decimals(requirement_mint);
+      const required_token_ata = getAssociatedTokenAddressSync(requirement_mint,raffle_pda,true,required_mint_token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+
+      keys.push({ isSigner: false, isWritable: true, pubkey: required_token_ata })
+      keys.push({ isSigner: false, isWritable: false, pubkey: requirement_mint })
+      keys.push({ isSigner: false, isWritable: false, pubkey: required_mint_token_program })
+    }
+
+    keys.push({ isSigner: false, isWritable: false, pubkey: SystemProgram.programId })
+    keys.push({ isSigner: false, isWritable: false, pubkey: ASSOCIATED_TOKEN_PROGRAM_ID })
+
+    const computeBudgetIx1 = ComputeBudgetProgram.setComputeUnitLimit({units:500000});
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys,
+      data: Buffer.from([241])
+    });
+
+    const message = new TransactionMessage({
+      instructions: [computeBudgetIx1,ix],
+      payerKey: payer.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash
+    }).compileToV0Message();
+
+    const tx = new VersionedTransaction(message);
+    tx.sign([payer]);
+
+    await connection.sendTransaction(tx);
+
+    return delay(600)
+}
+
+  export const close_series = async (creator:Keypair, series_id:bigint) => {
+
+    const series_pda = series_address(creator.publicKey, series_id);
+
+    const series_info = await connection.getAccountInfo(series_pda);
+    const series = borsh.deserialize(RaffleSeriesSchema,series_info?.data!) as RaffleSeries;
+
+    const keys:AccountMeta[] = [
+      { isSigner: true, isWritable: true, pubkey: creator.publicKey },
+      { isSigner: false, isWritable: true, pubkey: series_pda },
+    ];
+
+    if(series.template.reward_type != BigInt(1)){
+      const [, reward_mint] = await get_type_mint("rewtype", series.template.reward_type);
+      const [reward_token_program] = await get_token_program_and_decimals(reward_mint);
+
+      keys.push({ isSigner: false, isWritable: true, pubkey: getAssociatedTokenAddressSync(reward_mint,series_pda,true,reward_token_program,ASSOCIATED_TOKEN_PROGRAM_ID) })
+      keys.push({ isSigner: false, isWritable: true, pubkey: getAssociatedTokenAddressSync(reward_mint,creator.publicKey,false,reward_token_program,ASSOCIATED_TOKEN_PROGRAM_ID) })
+      keys.push({ isSigner: false, isWritable: false, pubkey: reward_mint })
+      keys.push({ isSigner: false, isWritable: false, pubkey: reward_token_program })
+    }
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys,
+      data: Buffer.from([242])
+    });
+
+    const message = new TransactionMessage({
+      instructions: [ix],
+      payerKey: creator.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash
+    }).compileToV0Message();
+
+    const tx = new VersionedTransaction(message);
+    tx.sign([creator]);
+
+    await connection.sendTransaction(tx);
+
+    return delay(600)
+}
+
+  //outside a series only the creator can pick the target raffle
+  export const rollover_pool = async (caller:Keypair, source_raffle_no:bigint, target_raffle_no:bigint) => {
+
+    const source_pda = PublicKey.findProgramAddressSync([Buffer.from("raffle"),numberToLEBytes8(source_raffle_no)],raffle_program)[0]
+    const target_pda = PublicKey.findProgramAddressSync([Buffer.from("raffle"),numberToLEBytes8(target_raffle_no)],raffle_program)[0]
+
+    const source_info = await connection.getAccountInfo(source_pda);
+    const source = borsh.deserialize(RaffleSchema,source_info?.data!) as Raffle;
+
+    const keys:AccountMeta[] = [
+      { isSigner: true, isWritable: true, pubkey: caller.publicKey },
+      { isSigner: false, isWritable: true, pubkey: source_pda },
+      { isSigner: false, isWritable: true, pubkey: target_pda },
+    ];
+
+    const reward_mint = new PublicKey(source.reward_mint);
+
+    if(!reward_mint.equals(SystemProgram.programId)){
+      const [reward_token_program] = await get_token_program_and_decimals(reward_mint);
+
+      keys.push({ isSigner: false, isWritable: true, pubkey: getAssociatedTokenAddressSync(reward_mint,source_pda,true,reward_token_program,ASSOCIATED_TOKEN_PROGRAM_ID) })
+      keys.push({ isSigner: false, isWritable: true, pubkey: getAssociatedTokenAddressSync(reward_mint,target_pda,true,reward_token_program,ASSOCIATED_TOKEN_PROGRAM_ID) })
+      keys.push({ isSigner: false, isWritable: false, pubkey: reward_mint })
+      keys.push({ isSigner: false, isWritable: false, pubkey: reward_token_program })
+    }
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys,
+      data: Buffer.from([250])
+    });
+
+    const message = new TransactionMessage({
+      instructions: [ix],
+      payerKey: caller.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash
+    }).compileToV0Message();
+
+    const tx = new VersionedTransaction(message);
+    tx.sign([caller]);
+
+    await connection.sendTransaction(tx);
+
+    return delay(600)
+}
+
+  export const collect_fee_token_initializer = async (raffle_no:bigint, initializer:Keypair, mint:PublicKey) => {
+
+
+    const le_bytes = numberToLEBytes8(raffle_no)
+
+    const raffle_pda = PublicKey.findProgramAddressSync([Buffer.from("raffle"),le_bytes],raffle_program)[0]
+
 
     const fee_collector_account = PublicKey.findProgramAddressSync([Buffer.from("fee_collector")], raffle_program)[0];
 console.log(fee_collector_account.toBase58())
+    const fee_ledger = PublicKey.findProgramAddressSync([Buffer.from("fee_ledger"),mint.toBytes()], raffle_program)[0];
     const mint_info = await connection.getAccountInfo(mint)
 
     const token_program = mint_info!.owner
@@ -321,22 +720,35 @@ console.log(fee_collector_account.toBase58())
     const initializer_ata = getAssociatedTokenAddressSync(mint,initializer.publicKey,false,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
 
 
-    const ix = new TransactionInstruction({
-      programId: raffle_program,
-      keys: [
+    const raffle_account_info = await connection.getAccountInfo(raffle_pda);
+    const raffle = borsh.deserialize(RaffleSchema,raffle_account_info?.data!) as Raffle;
+
+    const fee_currency = fee_currency_of(raffle, mint);
+
+    const keys:AccountMeta[] = [
         { isSigner: true, isWritable: true, pubkey: initializer.publicKey },
         { isSigner: false, isWritable: true, pubkey: raffle_pda },
-        { isSigner: false, isWritable: false, pubkey: term_account },
         { isSigner: false, isWritable: true, pubkey: fee_collector_account },
+        { isSigner: false, isWritable: true, pubkey: fee_ledger },
         { isSigner: false, isWritable: true, pubkey: fee_collector_ata },
         { isSigner: false, isWritable: true, pubkey: initializer_ata },
         { isSigner: false, isWritable: true, pubkey: raffle_ata },
         { isSigner: false, isWritable: false, pubkey: token_program },
         { isSigner: false, isWritable: false, pubkey: mint },
-        { isSigner: false, isWritable: false, pubkey: SystemProgram.programId },
-        { isSigner: false, isWritable: false, pubkey: ASSOCIATED_TOKEN_PROGRAM_ID },
-   ],
-      data: Buffer.from([200])
+    ];
+
+    for (const payee of raffle.payees) {
+      const payee_ata = getAssociatedTokenAddressSync(mint,new PublicKey(payee),false,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+      keys.push({ isSigner: false, isWritable: true, pubkey: payee_ata })
+    }
+
+    keys.push({ isSigner: false, isWritable: false, pubkey: SystemProgram.programId })
+    keys.push({ isSigner: false, isWritable: false, pubkey: ASSOCIATED_TOKEN_PROGRAM_ID })
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys,
+      data: Buffer.from(Uint8Array.of(200, ...borsh.serialize(FeeCurrencySchema, { fee_currency })))
     });
 
     const message = new TransactionMessage({
@@ -411,3 +823,35 @@ console.log(fee_collector_account.toBase58())
 return delay(600)
 }
 
+  //one time upgrade of a raffle created before term snapshots, anyone can pay for it
+  export const migrate_raffle = async (raffle_no:bigint, payer:Keypair) => {
+
+    const le_bytes = numberToLEBytes8(raffle_no)
+
+    const raffle_pda = PublicKey.findProgramAddressSync([Buffer.from("raffle"),le_bytes],raffle_program)[0]
+    const term_account = PublicKey.findProgramAddressSync([Buffer.from("term")],raffle_program)[0]
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys: [
+        { isSigner: true, isWritable: true, pubkey: payer.publicKey },
+        { isSigner: false, isWritable: true, pubkey: raffle_pda },
+        { isSigner: false, isWritable: false, pubkey: term_account },
+        { isSigner: false, isWritable: false, pubkey: SystemProgram.programId },
+      ],
+      data: Buffer.from([14])
+    });
+
+    const message = new TransactionMessage({
+      instructions: [ix],
+      payerKey: payer.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash
+    }).compileToV0Message();
+
+    const tx = new VersionedTransaction(message);
+    tx.sign([payer]);
+
+    await connection.sendTransaction(tx);
+
+    return delay(600)
+}
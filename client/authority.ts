@@ -2,7 +2,7 @@ import { getAssociatedTokenAddressSync, ASSOCIATED_TOKEN_PROGRAM_ID } from "@sol
 import { Keypair, PublicKey, TransactionInstruction, TransactionMessage, VersionedTransaction, AccountMeta, SYSVAR_RENT_PUBKEY, SystemProgram, AccountInfo } from "@solana/web3.js";
 import { entropy_account, raffle_program, rng_program, rng_program_fee_account } from "./accounts";
 import { connection } from "./connection";
-import { TermSchema, RewardFeeTypeSchema, Raffle, RaffleSchema, RewardFeeType, CallLimitSchema } from "./models";
+import { TermSchema, RewardFeeTypeSchema, RewardTypeSchema, FeeSchedule, FeeScheduleSchema, FeeWithdrawalSchema, Raffle, RaffleSchema, RewardFeeType, CallLimitSchema } from "./models";
 import { numberToLEBytes8 } from "./utils";
 import * as borsh from "borsh";
 import { get_token_program_and_decimals,  get_participation_pda_by_raffle_no_and_winner_no } from "./get_info";
@@ -11,12 +11,10 @@ function delay(ms: number): Promise<void> {
   return new Promise(resolve => setTimeout(resolve, ms));
 }
 
+  //only the treasury can be set here, authority keys are rotated with propose_authority and accept_authority
   export const set_config = async (
     authority:Keypair,
-    authority_1:PublicKey,
-    authority_2:PublicKey,
-    authority_3:PublicKey,
-    authority_4:PublicKey,
+    treasury:PublicKey,
   ) => {
   
     const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
@@ -26,11 +24,8 @@ function delay(ms: number): Promise<void> {
       programId: raffle_program,
       keys: [
         { isSigner: true, isWritable: false, pubkey: authority.publicKey },
-        { isSigner: false, isWritable: false, pubkey: authority_1 },//1
-        { isSigner: false, isWritable: false, pubkey: authority_2 },//2
-        { isSigner: false, isWritable: false, pubkey: authority_3 },//3
-        { isSigner: false, isWritable: false, pubkey: authority_4 },//4
         { isSigner: false, isWritable: true, pubkey: config_account },
+        { isSigner: false, isWritable: false, pubkey: treasury },
       ],
       data: Buffer.from([8])
     });
@@ -52,20 +47,59 @@ function delay(ms: number): Promise<void> {
     return delay(600)
   }
 
-  export const collect_fee = async (authority:Keypair) => {
+  //one time upgrade of a config created before governance, pause and treasury existed
+  export const migrate_config = async (authority:Keypair, treasury:PublicKey) => {
+
+    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys: [
+        { isSigner: true, isWritable: true, pubkey: authority.publicKey },
+        { isSigner: false, isWritable: true, pubkey: config_account },
+        { isSigner: false, isWritable: false, pubkey: treasury },
+        { isSigner: false, isWritable: false, pubkey: SystemProgram.programId },
+      ],
+      data: Buffer.from([13])
+    });
+
+    const message = new TransactionMessage({
+      instructions: [ix],
+      payerKey: authority.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash
+    }).compileToV0Message();
+
+    const tx = new VersionedTransaction(message);
+    tx.sign([authority]);
+
+    const sig = await connection.sendTransaction(tx);
+
+    console.log(sig)
+
+    return delay(600)
+  }
+
+  export const collect_fee = async (authority:Keypair,treasury:PublicKey,amount:bigint) => {
   
     const fee_account = PublicKey.findProgramAddressSync([Buffer.from("fee_collector")], raffle_program)[0];
   
+    const roles_account = PublicKey.findProgramAddressSync([Buffer.from("roles")],raffle_program)[0];
     const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
+    const fee_ledger = PublicKey.findProgramAddressSync([Buffer.from("fee_ledger"),SystemProgram.programId.toBytes()],raffle_program)[0];
+
+    const encoded = borsh.serialize(FeeWithdrawalSchema, { amount });
 
     const ix = new TransactionInstruction({
       programId: raffle_program,
       keys: [
         { isSigner: true, isWritable: true, pubkey: authority.publicKey },
         { isSigner: false, isWritable: true, pubkey: fee_account },
+        { isSigner: false, isWritable: false, pubkey: roles_account },
         { isSigner: false, isWritable: false, pubkey: config_account },
+        { isSigner: false, isWritable: true, pubkey: fee_ledger },
+        { isSigner: false, isWritable: true, pubkey: treasury },
       ],
-      data: Buffer.from([10])
+      data: Buffer.from(Uint8Array.of(10, ...encoded))
     });
   
     const message = new TransactionMessage({
@@ -82,32 +116,38 @@ function delay(ms: number): Promise<void> {
     return delay(600)
   }
 
-  export const collect_fee_token = async (authority:Keypair,participation_fee_mint:PublicKey) => {
+  export const collect_fee_token = async (authority:Keypair,participation_fee_mint:PublicKey,treasury:PublicKey,amount:bigint) => {
 
 
     const fee_collector = PublicKey.findProgramAddressSync([Buffer.from("fee_collector")], raffle_program)[0];
   
-    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
+    const roles_account = PublicKey.findProgramAddressSync([Buffer.from("roles")],raffle_program)[0];
 
     const mint_account_info = await connection.getAccountInfo(participation_fee_mint);
 
     const token_program = mint_account_info!.owner;
 
-    const authority_ata = getAssociatedTokenAddressSync(participation_fee_mint,authority.publicKey,false,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+    const treasury_ata = getAssociatedTokenAddressSync(participation_fee_mint,treasury,true,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
     const fee_collector_ata = getAssociatedTokenAddressSync(participation_fee_mint,fee_collector,true,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
+    const fee_ledger = PublicKey.findProgramAddressSync([Buffer.from("fee_ledger"),participation_fee_mint.toBytes()],raffle_program)[0];
+
+    const encoded = borsh.serialize(FeeWithdrawalSchema, { amount });
 
     const ix = new TransactionInstruction({
       programId: raffle_program,
       keys: [
         { isSigner: true, isWritable: true, pubkey: authority.publicKey },
-        { isSigner: true, isWritable: true, pubkey: authority_ata },
+        { isSigner: false, isWritable: true, pubkey: treasury_ata },
         { isSigner: false, isWritable: true, pubkey: fee_collector },
         { isSigner: false, isWritable: true, pubkey: fee_collector_ata },
         { isSigner: false, isWritable: true, pubkey: token_program },
         { isSigner: false, isWritable: true, pubkey: participation_fee_mint },
+        { isSigner: false, isWritable: false, pubkey: roles_account },
         { isSigner: false, isWritable: false, pubkey: config_account },
+        { isSigner: false, isWritable: true, pubkey: fee_ledger },
       ],
-      data: Buffer.from([20])
+      data: Buffer.from(Uint8Array.of(20, ...encoded))
     });
   
     const message = new TransactionMessage({
@@ -126,11 +166,11 @@ function delay(ms: number): Promise<void> {
 
   export const update_terms = async (authority:Keypair, newFee:bigint, expirationTime:bigint,maximumWinnerCount:bigint) => {
 
-    const initialized:number = 2;
+    const initialized:number = 14;
 
     const term = {
       initialized: initialized, 
-      fee_percent: newFee, 
+      fee_bps: newFee, 
       expiration_time: expirationTime, 
       maximum_winner_count: maximumWinnerCount, 
     }
@@ -139,6 +179,8 @@ function delay(ms: number): Promise<void> {
     let encoded = borsh.serialize(TermSchema, term);
   
     const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0]
+    const roles_account = PublicKey.findProgramAddressSync([Buffer.from("roles")],raffle_program)[0]
+    const pending_term_account = PublicKey.findProgramAddressSync([Buffer.from("pending_term")],raffle_program)[0]
 
   
     let concated = Uint8Array.of(9, ...encoded);
@@ -151,6 +193,9 @@ function delay(ms: number): Promise<void> {
         { isSigner: true, isWritable: true, pubkey: authority.publicKey },
         { isSigner: false, isWritable: true, pubkey: term_account },
         { isSigner: false, isWritable: false, pubkey: config_account },
+        { isSigner: false, isWritable: false, pubkey: roles_account },
+        { isSigner: false, isWritable: true, pubkey: pending_term_account },
+        { isSigner: false, isWritable: false, pubkey: SystemProgram.programId },
       ],
       data: Buffer.from(concated)
     });
@@ -169,17 +214,20 @@ function delay(ms: number): Promise<void> {
     return delay(600)
   }
 
-  export const close_participant_pda = async (authority:Keypair,accounts:PublicKey[]) => {
+  //every account must be a participant of raffle_no, the raffle has to be settled
+  export const close_participant_pda = async (authority:Keypair,raffle_no:bigint,accounts:PublicKey[]) => {
   
-    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
+    const roles_account = PublicKey.findProgramAddressSync([Buffer.from("roles")],raffle_program)[0];
+    const raffle_pda = PublicKey.findProgramAddressSync([Buffer.from("raffle"),numberToLEBytes8(raffle_no)],raffle_program)[0];
   
     const keys:AccountMeta[] = [];
 
     const authority_meta = { isSigner: true, isWritable: true, pubkey: authority.publicKey }
-    const config_account_meta = { isSigner: false, isWritable: false, pubkey: config_account }
+    const roles_account_meta = { isSigner: false, isWritable: false, pubkey: roles_account }
 
     keys.push(authority_meta)
-    keys.push(config_account_meta)
+    keys.push(roles_account_meta)
+    keys.push({ isSigner: false, isWritable: false, pubkey: raffle_pda })
 
     for (let index = 0; index < accounts.length; index++) {
       const element = accounts[index];
@@ -208,7 +256,8 @@ function delay(ms: number): Promise<void> {
   
   }
 
-  export const init_fee_type_account = async (authority:Keypair,participation_fee_mint:PublicKey,fee_type:bigint,decimals:number) => {
+  export const init_fee_type_account = async (authority:Keypair,participation_fee_mint:PublicKey,fee_type:bigint,decimals:number,
+    fee_bps:bigint|null = null,min_participation_fee:bigint = BigInt(0),max_participation_fee:bigint = BigInt(0)) => {
 
 
     const fee_collector_account = PublicKey.findProgramAddressSync([Buffer.from("fee_collector")], raffle_program)[0];
@@ -217,7 +266,7 @@ function delay(ms: number): Promise<void> {
     const fee_type_account = PublicKey.findProgramAddressSync([Buffer.from("feetype"),fee_type_bytes], raffle_program)[0];
 
 
-    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0]
+    const roles_account = PublicKey.findProgramAddressSync([Buffer.from("roles")],raffle_program)[0]
 
     const mint_account_info = await connection.getAccountInfo(participation_fee_mint);
 
@@ -230,6 +279,11 @@ function delay(ms: number): Promise<void> {
       mint: participation_fee_mint.toBytes(),
       decimals: decimals,
       no: fee_type,
+      enabled: 1,
+      has_fee_bps: fee_bps == null ? 0 : 1,
+      fee_bps: fee_bps ?? BigInt(0),
+      min_participation_fee,
+      max_participation_fee,
     }
 
 
@@ -249,7 +303,7 @@ function delay(ms: number): Promise<void> {
         { isSigner: false, isWritable: false, pubkey: participation_fee_mint },
         { isSigner: false, isWritable: false, pubkey: token_program },
         { isSigner: false, isWritable: false, pubkey: SYSVAR_RENT_PUBKEY },
-        { isSigner: false, isWritable: false, pubkey: config_account },
+        { isSigner: false, isWritable: false, pubkey: roles_account },
         { isSigner: false, isWritable: false, pubkey: SystemProgram.programId },
         { isSigner: false, isWritable: false, pubkey: ASSOCIATED_TOKEN_PROGRAM_ID },
       ],
@@ -271,13 +325,50 @@ function delay(ms: number): Promise<void> {
 
   }
 
+  export const set_fee_schedule = async (authority:Keypair,fee_type:bigint,schedule:FeeSchedule) => {
+
+    const fee_type_bytes = numberToLEBytes8(fee_type)
+    const fee_type_account = PublicKey.findProgramAddressSync([Buffer.from("feetype"),fee_type_bytes], raffle_program)[0];
+
+    const roles_account = PublicKey.findProgramAddressSync([Buffer.from("roles")],raffle_program)[0]
+
+    let encoded = borsh.serialize(FeeScheduleSchema, schedule);
+
+    let concated = Uint8Array.of(37, ...encoded);
+
+    const ix = new TransactionInstruction({
+      programId: raffle_program,
+      keys: [
+        { isSigner: true, isWritable: true, pubkey: authority.publicKey },
+        { isSigner: false, isWritable: true, pubkey: fee_type_account },
+        { isSigner: false, isWritable: false, pubkey: roles_account },
+        { isSigner: false, isWritable: false, pubkey: SystemProgram.programId },
+      ],
+      data: Buffer.from(concated)
+    });
+
+    const message = new TransactionMessage({
+      instructions: [ix],
+      payerKey: authority.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash
+    }).compileToV0Message();
+
+    const tx = new VersionedTransaction(message);
+    tx.sign([authority]);
+
+    await connection.sendTransaction(tx);
+
+    return delay(600)
+
+  }
+
   export const init_reward_type_account = async (authority:Keypair,participation_fee_mint:PublicKey,reward_type:bigint,decimals:number) => {
 
 
     const reward_type_bytes = numberToLEBytes8(reward_type)
     const reward_type_account = PublicKey.findProgramAddressSync([Buffer.from("rewtype"),reward_type_bytes], raffle_program)[0];
 
-    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0]
+    const roles_account = PublicKey.findProgramAddressSync([Buffer.from("roles")],raffle_program)[0]
 
 
     const feetypedata = {
@@ -287,7 +378,7 @@ function delay(ms: number): Promise<void> {
       no: reward_type,
     }
 
-    let encoded = borsh.serialize(RewardFeeTypeSchema, feetypedata);
+    let encoded = borsh.serialize(RewardTypeSchema, feetypedata);
 
     let concated = Uint8Array.of(36, ...encoded);
 
@@ -297,7 +388,7 @@ function delay(ms: number): Promise<void> {
       keys: [
         { isSigner: true, isWritable: true, pubkey: authority.publicKey },
         { isSigner: false, isWritable: true, pubkey: reward_type_account },
-        { isSigner: false, isWritable: false, pubkey: config_account },
+        { isSigner: false, isWritable: false, pubkey: roles_account },
         { isSigner: false, isWritable: false, pubkey: SystemProgram.programId },
       ],
       data: Buffer.from(concated)
@@ -327,7 +418,7 @@ function delay(ms: number): Promise<void> {
 
     console.log(raffle_pda.toBase58())
  
-    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
+    const roles_account = PublicKey.findProgramAddressSync([Buffer.from("roles")],raffle_program)[0];
 
     const keys:AccountMeta[] = [];
 
@@ -337,7 +428,7 @@ function delay(ms: number): Promise<void> {
     const rng_program_fee_account_meta = { isSigner: false, isWritable: true, pubkey: rng_program_fee_account };
     const rng_program_meta = { isSigner: false, isWritable: false, pubkey: rng_program };
     const system_program_meta = { isSigner: false, isWritable: false, pubkey: SystemProgram.programId };
-    const config_account_meta = { isSigner: false, isWritable: false, pubkey: config_account };
+    const roles_account_meta = { isSigner: false, isWritable: false, pubkey: roles_account };
 
     keys.push(authority_meta)
     keys.push(raffle_pda_meta)
@@ -345,19 +436,21 @@ function delay(ms: number): Promise<void> {
     keys.push(rng_program_fee_account_meta)
     keys.push(rng_program_meta)
     keys.push(system_program_meta)
-    keys.push(config_account_meta)
+    keys.push(roles_account_meta)
 
     const raffle_account_info = await connection.getAccountInfo(raffle_pda);
 
     const raffle = borsh.deserialize(RaffleSchema,raffle_account_info?.data!) as Raffle;
 
-    if (raffle.current_number_of_participants == BigInt(0)){
+    if (raffle.current_number_of_participants == BigInt(0) || raffle.current_number_of_participants < raffle.min_participants){
 
         const initializer = new PublicKey(raffle.initializer);
         const reward_mint = new PublicKey(raffle.reward_mint);
-        const [token_program,decimals] = await get_token_program_and_decimals(reward_mint)
-        const initializer_ata = getAssociatedTokenAddressSync(reward_mint,initializer,false,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
-        const raffle_ata = getAssociatedTokenAddressSync(reward_mint,raffle_pda,true,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+        const is_sol_reward = reward_mint.equals(SystemProgram.programId);
+        const [token_program,decimals] = is_sol_reward ? [SystemProgram.programId,9] : await get_token_program_and_decimals(reward_mint)
+        //SOL rewards go straight back to the initializer wallet
+        const initializer_ata = is_sol_reward ? initializer : getAssociatedTokenAddressSync(reward_mint,initializer,false,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+        const raffle_ata = is_sol_reward ? raffle_pda : getAssociatedTokenAddressSync(reward_mint,raffle_pda,true,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
 
 
         const initializer_ata_meta = { isSigner: false, isWritable: true, pubkey: initializer_ata };
@@ -117,7 +117,19 @@ export const get_raffle_by_raffle_no = async(raffle_no:bigint) => {
     console.log("raffle no = " + raffle.raffle_no.toString())
     console.log("raffle state = " + raffle.raffle_state.toString())
     console.log("current_number_of_participants = " + raffle.current_number_of_participants.toString())
-    console.log("participants_required = " + raffle.participants_required.toString())
+    console.log("max_participants = " + raffle.max_participants.toString())
+    console.log("min_participants = " + raffle.min_participants.toString())
+    console.log("start_time = " + raffle.start_time.toString())
+    console.log("series_id = " + raffle.series_id.toString())
+    console.log("round_no = " + raffle.round_no.toString())
+    console.log("rollover = " + raffle.rollover.toString())
+    console.log("rollover_amount = " + raffle.rollover_amount.toString())
+    console.log("fees_received = " + raffle.fees_received.toString())
+    console.log("fees_to_pool = " + raffle.fees_to_pool.toString())
+    console.log("pricing_mode = " + raffle.pricing_mode.toString())
+    for (const accepted of raffle.accepted_fees) {
+      console.log("accepted fee type " + accepted.fee_type.toString() + " price = " + accepted.price.toString() + " received = " + accepted.fees_received.toString())
+    }
     console.log("current_winner_count = " + raffle.current_winner_count.toString())
     console.log("winner_count = " + raffle.winner_count.toString())
     console.log("number_of_entitled_winners = " + raffle.number_of_entitled_winners.toString())
@@ -244,7 +256,7 @@ export const get_all_participation_accounts_by_this_address = async(initializer:
         {
           filters: [
             {
-              dataSize: 58,
+              dataSize: 107,
             },
             {
               memcmp: {
@@ -310,7 +322,7 @@ export const get_participation_account_by_raffle_no_and_winner_no = async(raffle
       {
         filters: [
           {
-            dataSize: 58,
+            dataSize: 107,
           },
           {
             memcmp: {
@@ -355,7 +367,7 @@ export const get_participation_pda_by_raffle_no_and_winner_no = async (raffle_no
       {
         filters: [
           {
-            dataSize: 58,
+            dataSize: 107,
           },
           {
             memcmp: {
@@ -437,7 +449,7 @@ export const get_all_reward_types = async () => {
         filters: [
 
           {
-            dataSize:42
+            dataSize:68
           },
           {
             memcmp: {
@@ -479,7 +491,7 @@ export const get_all_fee_types = async () => {
         filters: [
 
           {
-            dataSize:42
+            dataSize:68
           },
           {
             memcmp: {
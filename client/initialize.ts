@@ -11,6 +11,7 @@ function delay(ms: number): Promise<void> {
     authority_1:PublicKey,
     authority_2:PublicKey,
     authority_3:PublicKey,
+    treasury:PublicKey,
   ) => {
 
 
@@ -25,6 +26,7 @@ function delay(ms: number): Promise<void> {
         { isSigner: false, isWritable: false, pubkey: authority_2 },
         { isSigner: false, isWritable: false, pubkey: authority_3 },
         { isSigner: false, isWritable: true, pubkey: config_account[0] },
+        { isSigner: false, isWritable: false, pubkey: treasury },
         { isSigner: false, isWritable: false, pubkey: SystemProgram.programId },
       ],
       data: Buffer.from([7])
@@ -52,7 +54,7 @@ function delay(ms: number): Promise<void> {
     console.log("term account = " + term_account);
 
 
-    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0]
+    const roles_account = PublicKey.findProgramAddressSync([Buffer.from("roles")],raffle_program)[0]
 
 
     const ix = new TransactionInstruction({
@@ -60,7 +62,7 @@ function delay(ms: number): Promise<void> {
       keys: [
         { isSigner: true, isWritable: true, pubkey: authority.publicKey },
         { isSigner: false, isWritable: true, pubkey: term_account },
-        { isSigner: false, isWritable: false, pubkey: config_account },
+        { isSigner: false, isWritable: false, pubkey: roles_account },
         { isSigner: false, isWritable: false, pubkey: SystemProgram.programId },
       ],
       data: Buffer.from([6])
@@ -9,7 +9,7 @@
   raffle_name: number[]; // u8[32]
   raffle_no: bigint; // u64
   current_number_of_participants: bigint; // u64
-  participants_required: bigint; // u64
+  max_participants: bigint; // u64
   participation_fee: bigint; // u64
   participation_fee_mint: number[]; // u8[32]
   participation_fee_type: bigint; // u64
@@ -28,8 +28,67 @@
   current_winner_count: bigint; // u64
   number_of_entitled_winners: bigint; // u64
   fee_collected:number;
+  fee_bps: bigint; // u64
+  expiration_time: bigint; // u64
+  maximum_winner_count: bigint; // u64
+  created_at: bigint; // u64
+  referral_bps: bigint; // u64
+  payees: number[][]; // Vec<[u8;32]>
+  payee_shares_bps: bigint[]; // Vec<u64>
+  allowlist_root: number[]; // u8[32]
+  one_entry_per_nft: number; // u8
+  recheck_requirement_at_claim: number; // u8
+  max_entries_per_wallet: bigint; // u64
+  min_participants: bigint; // u64
+  refund_below_min: number; // u8
+  start_time: bigint; // u64
+  extension_count: number; // u8
+  series_id: bigint; // u64
+  round_no: bigint; // u64
+  rollover: number; // u8
+  claim_period: bigint; // u64
+  published_at: bigint; // u64
+  rewards_claimed: bigint; // u64
+  rollover_amount: bigint; // u64
+  rolled_over: number; // u8
+  pool_bps: bigint; // u64
+  pool_weights_bps: bigint[]; // Vec<u64>
+  fees_received: bigint; // u64
+  fees_to_pool: bigint; // u64
+  prize_shares_bps: bigint[]; // Vec<u64>
+  consolation_type: number; // u8
+  consolation_amount: bigint; // u64
+  consolation_escrow: bigint; // u64
+  consolation_reserved: bigint; // u64
+  pricing_mode: number; // u8
+  price_step_times: bigint[]; // Vec<u64>
+  price_step_prices: bigint[]; // Vec<u64>
+  end_price: bigint; // u64
+  attester: number[]; // u8[32]
+  accepted_fees: AcceptedFee[]; // Vec<AcceptedFee>
   bump:number;
   }
+
+  export interface AcceptedFee {
+  fee_type: bigint; // u64
+  mint: number[]; // u8[32]
+  decimals: number; // u8
+  price: bigint; // u64
+  fee_bps: bigint; // u64
+  fees_received: bigint; // u64
+  fee_collected: number; // u8
+  }
+  export const AcceptedFeeSchema = {
+  struct: {
+    fee_type: 'u64',
+    mint: { array: { type: 'u8', len: 32 } },
+    decimals: 'u8',
+    price: 'u64',
+    fee_bps: 'u64',
+    fees_received: 'u64',
+    fee_collected: 'u8',
+  },
+  };
   export const RaffleSchema = {
   struct: {
     raffle_state: 'u8',
@@ -40,7 +99,7 @@
     'raffle_name': { array: { type: 'u8', len: 32 } },
     raffle_no: 'u64',
     current_number_of_participants: 'u64',
-    participants_required: 'u64',
+    max_participants: 'u64',
     participation_fee: 'u64',
     'participation_fee_mint': { array: { type: 'u8', len: 32 } },
     participation_fee_type: 'u64',
@@ -59,6 +118,44 @@
     current_winner_count: 'u64',
     number_of_entitled_winners: 'u64',
     fee_collected: 'u8',
+    fee_bps: 'u64',
+    expiration_time: 'u64',
+    maximum_winner_count: 'u64',
+    created_at: 'u64',
+    referral_bps: 'u64',
+    'payees': { array: { type: { array: { type: 'u8', len: 32 } } } },
+    'payee_shares_bps': { array: { type: 'u64' } },
+    'allowlist_root': { array: { type: 'u8', len: 32 } },
+    one_entry_per_nft: 'u8',
+    recheck_requirement_at_claim: 'u8',
+    max_entries_per_wallet: 'u64',
+    min_participants: 'u64',
+    refund_below_min: 'u8',
+    start_time: 'u64',
+    extension_count: 'u8',
+    series_id: 'u64',
+    round_no: 'u64',
+    rollover: 'u8',
+    claim_period: 'u64',
+    published_at: 'u64',
+    rewards_claimed: 'u64',
+    rollover_amount: 'u64',
+    rolled_over: 'u8',
+    pool_bps: 'u64',
+    'pool_weights_bps': { array: { type: 'u64' } },
+    fees_received: 'u64',
+    fees_to_pool: 'u64',
+    'prize_shares_bps': { array: { type: 'u64' } },
+    consolation_type: 'u8',
+    consolation_amount: 'u64',
+    consolation_escrow: 'u64',
+    consolation_reserved: 'u64',
+    pricing_mode: 'u8',
+    'price_step_times': { array: { type: 'u64' } },
+    'price_step_prices': { array: { type: 'u64' } },
+    end_price: 'u64',
+    'attester': { array: { type: 'u8', len: 32 } },
+    'accepted_fees': { array: { type: AcceptedFeeSchema } },
     bump: 'u8',
   },};
 
@@ -69,6 +166,10 @@
   entitled: number; // u8
   prize_claimed: number; // u8
   index_in_winners: bigint; // u64
+  fee_paid: bigint; // u64
+  fee_currency: number; // u8
+  referrer: number[]; // u8[32]
+  referral_share: bigint; // u64
   }
   export const ParticipantSchema = {
   struct: {
@@ -78,19 +179,23 @@
     entitled: 'u8',
     prize_claimed: 'u8',
     index_in_winners: 'u64',
+    fee_paid: 'u64',
+    fee_currency: 'u8',
+    referrer: { array: { type: 'u8', len: 32 } },
+    referral_share: 'u64',
   },
   };
   
   export interface Term {
   initialized: number; // u8
-  fee_percent: bigint; // u64
+  fee_bps: bigint; // u64
   expiration_time: bigint; // u64
   maximum_winner_count: bigint; // u64
   }
   export const TermSchema = {
   struct: {
     initialized: 'u8',
-    fee_percent: 'u64',
+    fee_bps: 'u64',
     expiration_time: 'u64',
     maximum_winner_count: 'u64',
   },
@@ -101,6 +206,9 @@
   authority_2: number[]; // u8[32]
   authority_3: number[]; // u8[32]
   authority_4: number[]; // u8[32]
+  governance_enabled: number; // u8
+  paused: number; // u8
+  treasury: number[]; // u8[32]
   }
   export const ConfigSchema = {
   struct: {
@@ -108,6 +216,96 @@
     'authority_2': { array: { type: 'u8', len: 32 } },
     'authority_3': { array: { type: 'u8', len: 32 } },
     'authority_4': { array: { type: 'u8', len: 32 } },
+    governance_enabled: 'u8',
+    paused: 'u8',
+    'treasury': { array: { type: 'u8', len: 32 } },
+  },
+  };
+
+  export interface Roles {
+  initialized: number; // u8
+  drawers: number[][]; // Vec<[u8;32]>
+  treasurers: number[][]; // Vec<[u8;32]>
+  terms_admins: number[][]; // Vec<[u8;32]>
+  type_registrars: number[][]; // Vec<[u8;32]>
+  pausers: number[][]; // Vec<[u8;32]>
+  }
+  export const RolesSchema = {
+  struct: {
+    initialized: 'u8',
+    'drawers': { array: { type: { array: { type: 'u8', len: 32 } } } },
+    'treasurers': { array: { type: { array: { type: 'u8', len: 32 } } } },
+    'terms_admins': { array: { type: { array: { type: 'u8', len: 32 } } } },
+    'type_registrars': { array: { type: { array: { type: 'u8', len: 32 } } } },
+    'pausers': { array: { type: { array: { type: 'u8', len: 32 } } } },
+  },
+  };
+
+  export interface GovernanceSettings {
+  threshold: number; // u8
+  members: number[][]; // Vec<[u8;32]>
+  }
+  export const GovernanceSettingsSchema = {
+  struct: {
+    threshold: 'u8',
+    'members': { array: { type: { array: { type: 'u8', len: 32 } } } },
+  },
+  };
+
+  export interface Governance {
+  initialized: number; // u8
+  threshold: number; // u8
+  version: bigint; // u64
+  proposal_count: bigint; // u64
+  members: number[][]; // Vec<[u8;32]>
+  }
+  export const GovernanceSchema = {
+  struct: {
+    initialized: 'u8',
+    threshold: 'u8',
+    version: 'u64',
+    proposal_count: 'u64',
+    'members': { array: { type: { array: { type: 'u8', len: 32 } } } },
+  },
+  };
+
+  export interface ProposalData {
+  action: number; // u8
+  payload: Uint8Array; // Vec<u8>
+  }
+  export const ProposalDataSchema = {
+  struct: {
+    action: 'u8',
+    payload: { array: { type: 'u8' } },
+  },
+  };
+
+  export interface Proposal {
+  initialized: number; // u8
+  proposal_no: bigint; // u64
+  governance_version: bigint; // u64
+  proposer: number[]; // u8[32]
+  action: number; // u8
+  approvals: bigint; // u64
+  executed: number; // u8
+  payload: number[]; // Vec<u8>
+  }
+  export const ProposalSchema = {
+  struct: {
+    initialized: 'u8',
+    proposal_no: 'u64',
+    governance_version: 'u64',
+    'proposer': { array: { type: 'u8', len: 32 } },
+    action: 'u8',
+    approvals: 'u64',
+    executed: 'u8',
+    payload: { array: { type: 'u8' } },
+  },
+  };
+
+  export const ConfigTreasurySchema = {
+  struct: {
+    'treasury': { array: { type: 'u8', len: 32 } },
   },
   };
 
@@ -116,7 +314,7 @@
        is_unlimited_participant_allowed:'u8',
        'raffle_name':{ array: { type: 'u8', len: 32 } },
        participation_fee:'u64',
-       participants_required:'u64',
+       max_participants:'u64',
        raffle_time:'u64',
        multiple_participation_allowed: 'u8',
        participation_fee_type: 'u64',
@@ -129,6 +327,31 @@
        winner_count: 'u64',
        is_increasing_pool:'u8',
        'transfer_fee_to_pool': { array: { type: 'u64' } },
+       referral_bps:'u64',
+       'payees': { array: { type: { array: { type: 'u8', len: 32 } } } },
+       'payee_shares_bps': { array: { type: 'u64' } },
+       'allowlist_root': { array: { type: 'u8', len: 32 } },
+       one_entry_per_nft: 'u8',
+       recheck_requirement_at_claim: 'u8',
+       max_entries_per_wallet: 'u64',
+       min_participants: 'u64',
+       refund_below_min: 'u8',
+       start_time: 'u64',
+       rollover: 'u8',
+       claim_period: 'u64',
+       pool_bps: 'u64',
+       'pool_weights_bps': { array: { type: 'u64' } },
+       'prize_shares_bps': { array: { type: 'u64' } },
+       consolation_type: 'u8',
+       consolation_amount: 'u64',
+       consolation_funding: 'u64',
+       pricing_mode: 'u8',
+       'price_step_times': { array: { type: 'u64' } },
+       'price_step_prices': { array: { type: 'u64' } },
+       end_price: 'u64',
+       'attester': { array: { type: 'u8', len: 32 } },
+       'accepted_fee_types': { array: { type: 'u64' } },
+       'accepted_fee_prices': { array: { type: 'u64' } },
   }
   };
 
@@ -136,7 +359,7 @@
     is_unlimited_participant_allowed:number;
     raffle_name:number[];
     participation_fee:bigint;
-    participants_required:bigint;
+    max_participants:bigint;
     raffle_time:bigint;
     multiple_participation_allowed: number;
     participation_fee_type: bigint;
@@ -149,6 +372,31 @@
     winner_count: bigint;
     is_increasing_pool:number;
     transfer_fee_to_pool:bigint[];
+    referral_bps:bigint;
+    payees:number[][];
+    payee_shares_bps:bigint[];
+    allowlist_root:number[];
+    one_entry_per_nft:number;
+    recheck_requirement_at_claim:number;
+    max_entries_per_wallet:bigint;
+    min_participants:bigint;
+    refund_below_min:number;
+    start_time:bigint;
+    rollover:number;
+    claim_period:bigint;
+    pool_bps:bigint;
+    pool_weights_bps:bigint[];
+    prize_shares_bps:bigint[];
+    consolation_type:number;
+    consolation_amount:bigint;
+    consolation_funding:bigint;
+    pricing_mode:number;
+    price_step_times:bigint[];
+    price_step_prices:bigint[];
+    end_price:bigint;
+    attester:number[];
+    accepted_fee_types:bigint[];
+    accepted_fee_prices:bigint[];
   }
 
   export const CounterSchema = { 
@@ -167,8 +415,33 @@
     mint: number[];
     decimals: number; // u8
     no: bigint; // u64
+    enabled: number; // u8
+    has_fee_bps: number; // u8
+    fee_bps: bigint; // u64
+    min_participation_fee: bigint; // u64
+    max_participation_fee: bigint; // u64
   }
   export const RewardFeeTypeSchema = {
+  struct: {
+    initialized:'u8',
+    'mint': { array: { type: 'u8', len: 32 } },
+    decimals: 'u8',
+    no: 'u64',
+    enabled: 'u8',
+    has_fee_bps: 'u8',
+    fee_bps: 'u64',
+    min_participation_fee: 'u64',
+    max_participation_fee: 'u64',
+  },
+  };
+
+  export interface RewardType {
+    initialized:number;
+    mint: number[];
+    decimals: number; // u8
+    no: bigint; // u64
+  }
+  export const RewardTypeSchema = {
   struct: {
     initialized:'u8',
     'mint': { array: { type: 'u8', len: 32 } },
@@ -177,6 +450,23 @@
   },
   };
 
+  export interface FeeSchedule {
+    enabled: number; // u8
+    has_fee_bps: number; // u8
+    fee_bps: bigint; // u64
+    min_participation_fee: bigint; // u64
+    max_participation_fee: bigint; // u64
+  }
+  export const FeeScheduleSchema = {
+  struct: {
+    enabled: 'u8',
+    has_fee_bps: 'u8',
+    fee_bps: 'u64',
+    min_participation_fee: 'u64',
+    max_participation_fee: 'u64',
+  },
+  };
+
   export interface RaffleNameData {
     raffle_name: number[]; // u8[32]
   }  
@@ -200,4 +490,192 @@
   };
   export interface R  { 
     x: bigint, 
-  }
\ No newline at end of file
+  }
+  export interface AuthorityProposal {
+  slot: number; // u8
+  new_authority: number[]; // u8[32]
+  }
+  export const AuthorityProposalSchema = {
+  struct: {
+    slot: 'u8',
+    'new_authority': { array: { type: 'u8', len: 32 } },
+  },
+  };
+
+  export interface AuthorityRotation {
+  initialized: number; // u8
+  slot: number; // u8
+  current_authority: number[]; // u8[32]
+  pending_authority: number[]; // u8[32]
+  }
+  export const AuthorityRotationSchema = {
+  struct: {
+    initialized: 'u8',
+    slot: 'u8',
+    'current_authority': { array: { type: 'u8', len: 32 } },
+    'pending_authority': { array: { type: 'u8', len: 32 } },
+  },
+  };
+
+  export interface PendingTerm {
+  initialized: number; // u8
+  min_delay: bigint; // u64
+  term_pending: number; // u8
+  term_effective_at: bigint; // u64
+  fee_bps: bigint; // u64
+  expiration_time: bigint; // u64
+  maximum_winner_count: bigint; // u64
+  delay_pending: number; // u8
+  delay_effective_at: bigint; // u64
+  pending_min_delay: bigint; // u64
+  term_queued_at: bigint; // u64
+  }
+  export const PendingTermSchema = {
+  struct: {
+    initialized: 'u8',
+    min_delay: 'u64',
+    term_pending: 'u8',
+    term_effective_at: 'u64',
+    fee_bps: 'u64',
+    expiration_time: 'u64',
+    maximum_winner_count: 'u64',
+    delay_pending: 'u8',
+    delay_effective_at: 'u64',
+    pending_min_delay: 'u64',
+    term_queued_at: 'u64',
+  },
+  };
+
+  export const RaffleExtensionSchema = {
+  struct: {
+    new_raffle_time: 'u64',
+  },
+  };
+
+  export interface RaffleSeries {
+  initialized: number; // u8
+  creator: number[]; // u8[32]
+  series_id: bigint; // u64
+  round_no: bigint; // u64
+  current_raffle_no: bigint; // u64
+  active: number; // u8
+  duration: bigint; // u64
+  template: InitRaffle;
+  bump: number; // u8
+  }
+  export const RaffleSeriesSchema = {
+  struct: {
+    initialized: 'u8',
+    'creator': { array: { type: 'u8', len: 32 } },
+    series_id: 'u64',
+    round_no: 'u64',
+    current_raffle_no: 'u64',
+    active: 'u8',
+    duration: 'u64',
+    template: InitRaffleSchema,
+    bump: 'u8',
+  },
+  };
+
+  export const InitSeriesSchema = {
+  struct: {
+    series_id: 'u64',
+    duration: 'u64',
+    template: InitRaffleSchema,
+  },
+  };
+
+  export const TermDelaySchema = {
+  struct: {
+    min_delay: 'u64',
+  },
+  };
+
+  export const JoinRaffleSchema = {
+  struct: {
+    'referrer': { array: { type: 'u8', len: 32 } },
+    'proof': { array: { type: { array: { type: 'u8', len: 32 } } } },
+    allowed_entries: 'u64',
+    attestation_expiry: 'u64',
+  },
+  };
+
+  export interface NftEntry {
+  initialized: number; // u8
+  raffle_no: bigint; // u64
+  mint: number[]; // u8[32]
+  bump: number; // u8
+  }
+  export const NftEntrySchema = {
+  struct: {
+    initialized: 'u8',
+    raffle_no: 'u64',
+    'mint': { array: { type: 'u8', len: 32 } },
+    bump: 'u8',
+  },
+  };
+
+  export interface EntryCounter {
+  initialized: number; // u8
+  raffle_no: bigint; // u64
+  wallet: number[]; // u8[32]
+  entries: bigint; // u64
+  bump: number; // u8
+  }
+  export const EntryCounterSchema = {
+  struct: {
+    initialized: 'u8',
+    raffle_no: 'u64',
+    'wallet': { array: { type: 'u8', len: 32 } },
+    entries: 'u64',
+    bump: 'u8',
+  },
+  };
+
+  export interface FeeLedger {
+  initialized: number; // u8
+  mint: number[]; // u8[32]
+  accrued: bigint; // u64
+  withdrawn: bigint; // u64
+  bump: number; // u8
+  }
+  export const FeeLedgerSchema = {
+  struct: {
+    initialized: 'u8',
+    'mint': { array: { type: 'u8', len: 32 } },
+    accrued: 'u64',
+    withdrawn: 'u64',
+    bump: 'u8',
+  },
+  };
+
+  export const FeeWithdrawalSchema = {
+  struct: {
+    amount: 'u64',
+  },
+  };
+
+  export const FeeCurrencySchema = {
+  struct: {
+    fee_currency: 'u8',
+  },
+  };
+
+  export interface Referral {
+  initialized: number; // u8
+  referrer: number[]; // u8[32]
+  mint: number[]; // u8[32]
+  accrued: bigint; // u64
+  withdrawn: bigint; // u64
+  bump: number; // u8
+  }
+  export const ReferralSchema = {
+  struct: {
+    initialized: 'u8',
+    'referrer': { array: { type: 'u8', len: 32 } },
+    'mint': { array: { type: 'u8', len: 32 } },
+    accrued: 'u64',
+    withdrawn: 'u64',
+    bump: 'u8',
+  },
+  };
@@ -6,14 +6,16 @@ import {
     SystemProgram,
     TransactionInstruction,
     SYSVAR_RENT_PUBKEY,
+    SYSVAR_INSTRUCTIONS_PUBKEY,
+    Ed25519Program,
     AccountMeta,
     ComputeBudgetProgram,
   } from "@solana/web3.js";
   import * as borsh from 'borsh';
-  import {  Raffle, RaffleSchema,  } from "./models";
+  import {  JoinRaffleSchema, Raffle, RaffleSchema,  } from "./models";
   import {connection} from './connection';
-  import { raffle_program, } from "./accounts";
-  import {  numberToLEBytes8 } from "./utils";
+  import { metadata_program, raffle_program, } from "./accounts";
+  import {  deserialize_participation_account_data, numberToLEBytes8 } from "./utils";
   import { getAssociatedTokenAddressSync, ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID,  } from "@solana/spl-token";
   import {  get_participation_fee_mint,  get_token_program_and_decimals } from "./get_info";
 
@@ -23,7 +25,14 @@ import {
 
 
 
-  export const join_raffle = async (raffle_no:bigint, participant:Keypair) => {
+  //what the raffle's attester signs off-chain before handing out the signature
+  export const attestation_message = (raffle_no:bigint, participant:PublicKey, expiry:bigint) => {
+    return Buffer.concat([Buffer.from(numberToLEBytes8(raffle_no)), participant.toBuffer(), Buffer.from(numberToLEBytes8(expiry))])
+  }
+
+  export const join_raffle = async (raffle_no:bigint, participant:Keypair, referrer:PublicKey|null = null,
+    proof:number[][] = [], allowed_entries:bigint = BigInt(0), nft_mint:PublicKey|null = null,
+    attestation_expiry:bigint = BigInt(0), attestation_signature:Uint8Array|null = null, fee_currency:number = 0) => {
 
     const raffle_no_le_byte = numberToLEBytes8(raffle_no)
 
@@ -73,18 +82,32 @@ import {
     keys.push(participant_pda_meta)
 
 
-    if (raffle.participation_fee_type == BigInt(1)){
+    const config_account = PublicKey.findProgramAddressSync([Buffer.from("config")],raffle_program)[0];
+    const config_account_meta = { isSigner: false, isWritable: false, pubkey: config_account }
+
+    let fee_mint:PublicKey = SystemProgram.programId;
+    let fee_token_program:PublicKey = TOKEN_PROGRAM_ID;
+
+    //0 pays in participation_fee_mint, n in raffle.accepted_fees[n-1]
+    const accepted = fee_currency == 0 ? null : raffle.accepted_fees[fee_currency - 1];
+    const fee_type_no = accepted == null ? raffle.participation_fee_type : accepted.fee_type;
+
+    if (fee_type_no == BigInt(1)){
       console.log("solana fee")
         const participation_fee_mint_meta = { isSigner: false, isWritable: false, pubkey: SystemProgram.programId }
 
         keys.push(participation_fee_mint_meta)
+        keys.push(config_account_meta)
 
     }else{
-        const participation_fee_mint = await get_participation_fee_mint(raffle.participation_fee_type)
+        const participation_fee_mint = accepted == null ? await get_participation_fee_mint(raffle.participation_fee_type) : new PublicKey(accepted.mint)
         const participation_fee_mint_meta = { isSigner: false, isWritable: false, pubkey: participation_fee_mint }
         keys.push(participation_fee_mint_meta)
+        keys.push(config_account_meta)
 
         const [token_program,decimals] = await get_token_program_and_decimals(participation_fee_mint)
+        fee_mint = participation_fee_mint;
+        fee_token_program = token_program;
         const participant_ata = getAssociatedTokenAddressSync(participation_fee_mint,participant.publicKey,false,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
         const raffle_ata = getAssociatedTokenAddressSync(participation_fee_mint,raffle_pda,true,token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
 
@@ -117,22 +140,79 @@ import {
       keys.push(participant_requirement_ata_meta)
       keys.push(raffle_requirement_ata_meta)
       keys.push(required_mint_token_program_meta)
+    }else if (raffle.requirement_to_participate == 2){
+      const nft_token_program = (await connection.getAccountInfo(nft_mint!))?.owner!;
+      const participant_nft_ata = getAssociatedTokenAddressSync(nft_mint!,participant.publicKey,false,nft_token_program,ASSOCIATED_TOKEN_PROGRAM_ID);
+      const nft_metadata = PublicKey.findProgramAddressSync([Buffer.from("metadata"),metadata_program.toBytes(),nft_mint!.toBytes()],metadata_program)[0];
+
+      keys.push({ isSigner: false, isWritable: false, pubkey: nft_mint! })
+      keys.push({ isSigner: false, isWritable: false, pubkey: participant_nft_ata })
+      keys.push({ isSigner: false, isWritable: false, pubkey: nft_metadata })
+
+      if (raffle.one_entry_per_nft == 1){
+        const nft_entry = PublicKey.findProgramAddressSync([Buffer.from("nft"),raffle_no_le_byte,nft_mint!.toBytes()],raffle_program)[0];
+        keys.push({ isSigner: false, isWritable: true, pubkey: nft_entry })
+      }
+    }else if (raffle.requirement_to_participate == 3){
+      const requirement_mint = new PublicKey(raffle.requirement_mint);
+      const required_mint_token_program = (await connection.getAccountInfo(requirement_mint))?.owner!;
+      const participant_requirement_ata = getAssociatedTokenAddressSync(requirement_mint,participant.publicKey,false,required_mint_token_program,ASSOCIATED_TOKEN_PROGRAM_ID);
+      keys.push({ isSigner: false, isWritable: false, pubkey: participant_requirement_ata })
+    }
+
+    if (referrer != null && raffle.referral_bps != BigInt(0)){
+      const referral_pda = PublicKey.findProgramAddressSync([Buffer.from("referral"),referrer.toBytes(),fee_mint.toBytes()],raffle_program)[0];
+      keys.push({ isSigner: false, isWritable: true, pubkey: referral_pda })
+
+      if (fee_type_no != BigInt(1)){
+        const referral_ata = getAssociatedTokenAddressSync(fee_mint,referral_pda,true,fee_token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+        keys.push({ isSigner: false, isWritable: true, pubkey: referral_ata })
+        keys.push({ isSigner: false, isWritable: false, pubkey: SYSVAR_RENT_PUBKEY })
+      }
+    }
+
+    const has_allowlist_cap = raffle.allowlist_root.some((b) => b != 0) && allowed_entries != BigInt(0);
+    if (raffle.max_entries_per_wallet != BigInt(0) || has_allowlist_cap){
+      const entry_counter = PublicKey.findProgramAddressSync([Buffer.from("entries"),raffle_no_le_byte,participant.publicKey.toBytes()],raffle_program)[0];
+      keys.push({ isSigner: false, isWritable: true, pubkey: entry_counter })
+    }
+
+    const instructions:TransactionInstruction[] = [ComputeBudgetProgram.setComputeUnitLimit({units:300000})];
+
+    //the signature is checked by the ed25519 program in the instruction right before the join
+    if (raffle.attester.some((b) => b != 0)){
+      keys.push({ isSigner: false, isWritable: false, pubkey: SYSVAR_INSTRUCTIONS_PUBKEY })
+
+      instructions.push(Ed25519Program.createInstructionWithPublicKey({
+        publicKey: Uint8Array.from(raffle.attester),
+        message: attestation_message(raffle_no, participant.publicKey, attestation_expiry),
+        signature: attestation_signature!,
+      }))
     }
 
     const system_program_program_meta = { isSigner: false, isWritable: false, pubkey: SystemProgram.programId }
     keys.push(system_program_program_meta)
-    
-    const computeBudgetIx1 = ComputeBudgetProgram.setComputeUnitLimit({units:300000});
 
 
+    const join_raffle_data = {
+      referrer: Array.from((referrer ?? PublicKey.default).toBytes()),
+      proof,
+      allowed_entries,
+      attestation_expiry,
+    }
+
+    const serialized = borsh.serialize(JoinRaffleSchema, join_raffle_data);
+
     const ix = new TransactionInstruction({
       programId: raffle_program,
       keys,
-      data: Buffer.from([1])
+      data: Buffer.from(Uint8Array.of(1, ...serialized))
     });
 
+    instructions.push(ix)
+
     const message = new TransactionMessage({
-      instructions: [computeBudgetIx1,ix],
+      instructions,
       payerKey: participant.publicKey,
       recentBlockhash: (await connection.getLatestBlockhash()).blockhash
     }).compileToV0Message();
@@ -245,6 +325,57 @@ import {
       keys.push(req_mint_meta)
       keys.push(req_mint_token_program_meta)
 
+    }else if (raffle.requirement_to_participate == 3 && raffle.recheck_requirement_at_claim == 1){
+
+      const req_mint = new PublicKey(raffle.requirement_mint);
+      const req_mint_token_program = (await connection.getAccountInfo(req_mint))?.owner!
+      const participant_req_ata = getAssociatedTokenAddressSync(req_mint,participant.publicKey,false,req_mint_token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+
+      keys.push({isSigner: false, isWritable: false, pubkey: participant_req_ata})
+    }
+
+    const winner_pda_info = await connection.getAccountInfo(winner_pda!);
+    const winner = deserialize_participation_account_data(winner_pda_info!);
+
+    //raffle did not reach min_participants, the participation fee is refunded.
+    //non-winners of a published raffle get their consolation prize from the same accounts
+    const gets_fee_payout = raffle.raffle_state == 4 || (raffle.raffle_state == 3 && raffle.consolation_type != 0 && winner.entitled == 0);
+
+    //refunds are paid in the currency the participant joined with
+    const accepted = winner.fee_currency == 0 ? null : raffle.accepted_fees[winner.fee_currency - 1];
+    const fee_type_no = accepted == null ? raffle.participation_fee_type : accepted.fee_type;
+
+    if (gets_fee_payout && fee_type_no != BigInt(1)){
+
+      const fee_mint = accepted == null ? await get_participation_fee_mint(raffle.participation_fee_type) : new PublicKey(accepted.mint)
+      const fee_token_program = (await connection.getAccountInfo(fee_mint))?.owner!
+      const raffle_fee_ata = getAssociatedTokenAddressSync(fee_mint,raffle_pda,true,fee_token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+      const participant_fee_ata = getAssociatedTokenAddressSync(fee_mint,participant.publicKey,false,fee_token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+
+      keys.push({isSigner: false, isWritable: true, pubkey: raffle_fee_ata})
+      keys.push({isSigner: false, isWritable: true, pubkey: participant_fee_ata})
+      keys.push({isSigner: false, isWritable: false, pubkey: fee_mint})
+      keys.push({isSigner: false, isWritable: false, pubkey: fee_token_program})
+    }
+
+    //a referral share held by a refund_below_min raffle is released to the referrer once it is published
+    if (raffle.raffle_state == 3 && winner.referral_share != BigInt(0)){
+
+      const fee_mint = accepted == null ? await get_participation_fee_mint(raffle.participation_fee_type) : new PublicKey(accepted.mint)
+      const referral_pda = PublicKey.findProgramAddressSync([Buffer.from("referral"),Buffer.from(winner.referrer),fee_mint.toBytes()],raffle_program)[0];
+
+      keys.push({isSigner: false, isWritable: true, pubkey: referral_pda})
+
+      if (fee_type_no != BigInt(1)){
+        const fee_token_program = (await connection.getAccountInfo(fee_mint))?.owner!
+        const raffle_fee_ata = getAssociatedTokenAddressSync(fee_mint,raffle_pda,true,fee_token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+        const referral_ata = getAssociatedTokenAddressSync(fee_mint,referral_pda,true,fee_token_program,ASSOCIATED_TOKEN_PROGRAM_ID)
+
+        keys.push({isSigner: false, isWritable: true, pubkey: raffle_fee_ata})
+        keys.push({isSigner: false, isWritable: true, pubkey: referral_ata})
+        keys.push({isSigner: false, isWritable: false, pubkey: fee_mint})
+        keys.push({isSigner: false, isWritable: false, pubkey: fee_token_program})
+      }
     }
     keys.push(SystemProgram_meta)
     keys.push(ASSOCIATED_TOKEN_PROGRAM_ID_meta)
@@ -49,7 +49,11 @@ export function deserialize_raffle_account_data(account_info:AccountInfo<Buffer>
 
 export function deserialize_participation_account_data(account_info:AccountInfo<Buffer>){
 
-    const participation = deserialize(ParticipantSchema,account_info.data) as Participant;
+    //older participant accounts are shorter, the fields they lack read as zero
+    const data = Buffer.alloc(107);
+    account_info.data.copy(data);
+
+    const participation = deserialize(ParticipantSchema,data) as Participant;
 
     return participation;
 }
@@ -66,6 +70,12 @@ export function deserialize_term_account_data(account_info:AccountInfo<Buffer>){
 
     const terms = deserialize(TermSchema,account_info.data) as Term;
 
+    //terms written before basis points still hold the fee in percent
+    if (terms.initialized == 2) {
+        terms.fee_bps = terms.fee_bps * BigInt(100);
+        terms.initialized = 14;
+    }
+
     console.log(terms.expiration_time)
     
     return terms;
@@ -0,0 +1,90 @@
+//Layout of an ed25519 program instruction carrying one signature:
+//count u8, padding u8, then seven u16 offsets, followed by the data they point to.
+const OFFSETS_START: usize = 2;
+const OFFSETS_LEN: usize = 14;
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+//The attester signs raffle_no, the participant wallet and the expiry, integers little endian.
+pub fn message(raffle_no: u64, participant: &[u8;32], expiry: u64) -> Vec<u8> {
+
+    let mut message: Vec<u8> = Vec::with_capacity(48);
+    message.extend_from_slice(&raffle_no.to_le_bytes());
+    message.extend_from_slice(participant);
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+//Checks that an ed25519 program instruction holds a single signature by attester over message.
+//The ed25519 program has already verified the signature itself when the transaction ran.
+//All offsets must point into that same instruction, otherwise the key or message could be
+//taken from another instruction of the transaction.
+pub fn verify_ed25519_data(data: &[u8], attester: &[u8;32], message: &[u8]) -> bool {
+
+    if data.first() != Some(&1) {return false;}
+
+    let offsets: Vec<u16> = match data.get(OFFSETS_START..OFFSETS_START + OFFSETS_LEN) {
+        Some(bytes) => bytes.chunks(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect(),
+        None => return false,
+    };
+
+    let (signature_ix, public_key_offset, public_key_ix) = (offsets[1], offsets[2] as usize, offsets[3]);
+    let (message_offset, message_size, message_ix) = (offsets[4] as usize, offsets[5] as usize, offsets[6]);
+
+    if signature_ix != CURRENT_INSTRUCTION || public_key_ix != CURRENT_INSTRUCTION || message_ix != CURRENT_INSTRUCTION {
+        return false;
+    }
+
+    let public_key: Option<&[u8]> = data.get(public_key_offset..public_key_offset + 32);
+    let signed_message: Option<&[u8]> = data.get(message_offset..message_offset + message_size);
+
+    public_key == Some(&attester[..]) && signed_message == Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //same layout the ed25519 program helpers produce: offsets, key, signature, message
+    fn instruction_data(public_key: &[u8;32], message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_offset: u16 = signature_offset + 64;
+
+        let mut data: Vec<u8> = vec![1, 0];
+        for value in [signature_offset, instruction_index, public_key_offset, instruction_index,
+            message_offset, message.len() as u16, instruction_index] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(public_key);
+        data.extend_from_slice(&[7; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn accepts_attester_signature_over_the_entry() {
+        let message: Vec<u8> = message(5, &[2; 32], 1_000);
+        assert_eq!(message.len(), 48);
+        assert!(verify_ed25519_data(&instruction_data(&[9; 32], &message, u16::MAX), &[9; 32], &message));
+    }
+
+    #[test]
+    fn rejects_other_key_or_message() {
+        let signed: Vec<u8> = message(5, &[2; 32], 1_000);
+        let data: Vec<u8> = instruction_data(&[9; 32], &signed, u16::MAX);
+        assert!(!verify_ed25519_data(&data, &[8; 32], &signed));
+        assert!(!verify_ed25519_data(&data, &[9; 32], &message(6, &[2; 32], 1_000)));
+        assert!(!verify_ed25519_data(&data, &[9; 32], &message(5, &[3; 32], 1_000)));
+        assert!(!verify_ed25519_data(&data, &[9; 32], &message(5, &[2; 32], 2_000)));
+    }
+
+    #[test]
+    fn rejects_data_from_other_instructions_and_short_data() {
+        let signed: Vec<u8> = message(5, &[2; 32], 1_000);
+        assert!(!verify_ed25519_data(&instruction_data(&[9; 32], &signed, 0), &[9; 32], &signed));
+        let data: Vec<u8> = instruction_data(&[9; 32], &signed, u16::MAX);
+        assert!(!verify_ed25519_data(&data[..20], &[9; 32], &signed));
+        assert!(!verify_ed25519_data(&data[..data.len() - 1], &[9; 32], &signed));
+        assert!(!verify_ed25519_data(&[], &[9; 32], &signed));
+    }
+}
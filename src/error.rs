@@ -84,6 +84,129 @@ pub enum RaffleProgramError {
   #[error("invalid mint")]//25
   InvalidMint,
 
+  #[error("config changes require a governance proposal")]//26
+  GovernanceEnabled,
+
+  #[error("invalid governance account")]//27
+  InvalidGovernance,
+
+  #[error("invalid proposal account")]//28
+  InvalidProposal,
+
+  #[error("signer is not a governance member")]//29
+  NotGovernanceMember,
+
+  #[error("proposal does not have enough approvals")]//30
+  ThresholdNotReached,
+
+  #[error("invalid roles account")]//31
+  InvalidRoles,
+
+  #[error("program is paused")]//32
+  ProgramPaused,
+
+  #[error("invalid authority slot")]//33
+  InvalidAuthoritySlot,
+
+  #[error("invalid authority rotation account")]//34
+  InvalidRotation,
+
+  #[error("invalid pending term account")]//35
+  InvalidPendingTerm,
+
+  #[error("pending term change is not effective yet")]//36
+  TermNotEffective,
+
+  #[error("invalid referral")]//37
+  InvalidReferral,
+
+  #[error("token account has the wrong owner or balance")]//38
+  InvalidTokenAccount,
+
+  #[error("invalid revenue split")]//39
+  InvalidRevenueSplit,
+
+  #[error("fee type is disabled")]//40
+  FeeTypeDisabled,
+
+  #[error("participation fee outside of fee type bounds")]//41
+  InvalidParticipationFee,
+
+  #[error("invalid fee ledger")]//42
+  InvalidFeeLedger,
+
+  #[error("invalid treasury")]//43
+  InvalidTreasury,
+
+  #[error("withdrawal exceeds available fees")]//44
+  InsufficientFees,
+
+  #[error("wallet is not on the allowlist")]//45
+  NotAllowlisted,
+
+  #[error("entry limit reached")]//46
+  EntryLimitReached,
+
+  #[error("invalid entry counter")]//47
+  InvalidEntryCounter,
+
+  #[error("nft is not part of the required collection")]//48
+  InvalidCollection,
+
+  #[error("nft was already used to enter this raffle")]//49
+  NftAlreadyUsed,
+
+  #[error("invalid participation requirement")]//50
+  InvalidRequirement,
+
+  #[error("participation requirement not met")]//51
+  RequirementNotMet,
+
+  #[error("minimum participants above the maximum")]//52
+  InvalidParticipantLimits,
+
+  #[error("raffle has not started yet")]//53
+  RaffleNotStarted,
+
+  #[error("raffle can not be extended")]//54
+  InvalidExtension,
+
+  #[error("invalid raffle series")]//55
+  InvalidSeries,
+
+  #[error("previous round of the series is not settled")]//56
+  RoundNotSettled,
+
+  #[error("series escrow can not fund the next round")]//57
+  SeriesNotFunded,
+
+  #[error("claim period is over")]//58
+  PrizeExpired,
+
+  #[error("invalid rollover")]//59
+  InvalidRollover,
+
+  #[error("invalid pool split")]//60
+  InvalidPoolSplit,
+
+  #[error("invalid prize shares")]//61
+  InvalidPrizeShares,
+
+  #[error("invalid consolation prize")]//62
+  InvalidConsolation,
+
+  #[error("invalid price schedule")]//63
+  InvalidPriceSchedule,
+
+  #[error("missing or invalid attestation")]//64
+  InvalidAttestation,
+
+  #[error("attestation expired")]//65
+  AttestationExpired,
+
+  #[error("invalid accepted fee currencies")]//66
+  InvalidAcceptedFees,
+
 }
 
 impl From<RaffleProgramError> for ProgramError {
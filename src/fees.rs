@@ -0,0 +1,139 @@
+use crate::error::RaffleProgramError::{ArithmeticError, InvalidPrizeShares, InvalidRevenueSplit, InvalidTerms};
+use solana_program::program_error::ProgramError;
+
+//fees and shares are expressed in basis points, 10000 = 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+//Rounding rule: the protocol fee is rounded down and the remainder always goes to the creator.
+//The same split is used for SOL and token fees, so a raffle never pays more fee than fee_bps allows.
+pub fn split_fee(collected_value: u64, fee_bps: u64) -> Result<(u64, u64), ProgramError> {
+
+    if fee_bps > BPS_DENOMINATOR {return Err(InvalidTerms.into());}
+
+    let total_fee: u64 = bps_share(collected_value, fee_bps)?;
+    let transfer_to_initializer: u64 = collected_value.checked_sub(total_fee).ok_or(ArithmeticError)?;
+
+    Ok((total_fee, transfer_to_initializer))
+}
+
+//amount * bps / 10000 rounded down, computed in u128 so large amounts do not overflow
+pub fn bps_share(amount: u64, bps: u64) -> Result<u64, ProgramError> {
+
+    let share: u128 = (amount as u128)
+        .checked_mul(bps as u128).ok_or(ArithmeticError)?
+        .checked_div(BPS_DENOMINATOR as u128).ok_or(ArithmeticError)?;
+
+    Ok(u64::try_from(share).map_err(|_| ArithmeticError)?)
+}
+
+//Splits the creator share by basis points, shares must add up to 10000.
+//Rounding dust from every share goes to the first payee.
+pub fn split_among_payees(amount: u64, shares_bps: &[u64]) -> Result<Vec<u64>, ProgramError> {
+
+    let total_bps: u64 = shares_bps.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+    if shares_bps.is_empty() || total_bps != BPS_DENOMINATOR {return Err(InvalidRevenueSplit.into());}
+
+    let mut amounts: Vec<u64> = Vec::with_capacity(shares_bps.len());
+    for share_bps in shares_bps {
+        amounts.push(bps_share(amount, *share_bps)?);
+    }
+
+    let distributed: u64 = amounts.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+    amounts[0] = amounts[0].checked_add(amount.checked_sub(distributed).ok_or(ArithmeticError)?).ok_or(ArithmeticError)?;
+
+    Ok(amounts)
+}
+
+//Splits amount in proportion to weights that do not need to add up to 10000.
+//Used for prize shares when fewer slots than planned got a winner. Dust goes to the first weight.
+pub fn split_by_weights(amount: u64, weights: &[u64]) -> Result<Vec<u64>, ProgramError> {
+
+    let total_weight: u64 = weights.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+    if total_weight == 0 {return Err(InvalidPrizeShares.into());}
+
+    let mut amounts: Vec<u64> = Vec::with_capacity(weights.len());
+    for weight in weights {
+        let share: u128 = (amount as u128)
+            .checked_mul(*weight as u128).ok_or(ArithmeticError)?
+            .checked_div(total_weight as u128).ok_or(ArithmeticError)?;
+        amounts.push(u64::try_from(share).map_err(|_| ArithmeticError)?);
+    }
+
+    let distributed: u64 = amounts.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+    amounts[0] = amounts[0].checked_add(amount.checked_sub(distributed).ok_or(ArithmeticError)?).ok_or(ArithmeticError)?;
+
+    Ok(amounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_is_taken_from_the_whole_amount() {
+        //the old percent math charged 2.5% of 100 here instead of 199
+        assert_eq!(split_fee(199, 250).unwrap(), (4, 195));
+        assert_eq!(split_fee(10_000, 250).unwrap(), (250, 9_750));
+    }
+
+    #[test]
+    fn remainder_goes_to_creator() {
+        assert_eq!(split_fee(1, 9_999).unwrap(), (0, 1));
+        assert_eq!(split_fee(3, 5_000).unwrap(), (1, 2));
+        assert_eq!(split_fee(9_999, 1).unwrap(), (0, 9_999));
+        assert_eq!(split_fee(10_001, 1).unwrap(), (1, 10_000));
+    }
+
+    #[test]
+    fn zero_and_full_fee() {
+        assert_eq!(split_fee(0, 250).unwrap(), (0, 0));
+        assert_eq!(split_fee(12_345, 0).unwrap(), (0, 12_345));
+        assert_eq!(split_fee(12_345, BPS_DENOMINATOR).unwrap(), (12_345, 0));
+    }
+
+    #[test]
+    fn large_amounts_do_not_overflow() {
+        let (fee, rest) = split_fee(u64::MAX, 250).unwrap();
+        assert_eq!(fee, ((u64::MAX as u128) * 250 / 10_000) as u64);
+        assert_eq!(fee + rest, u64::MAX);
+        assert_eq!(split_fee(u64::MAX, BPS_DENOMINATOR).unwrap(), (u64::MAX, 0));
+    }
+
+    #[test]
+    fn fee_above_100_percent_is_rejected() {
+        assert!(split_fee(100, BPS_DENOMINATOR + 1).is_err());
+    }
+
+    #[test]
+    fn payee_split_gives_dust_to_first_payee() {
+        assert_eq!(split_among_payees(100, &[3_333, 3_333, 3_334]).unwrap(), vec![34, 33, 33]);
+        assert_eq!(split_among_payees(1, &[5_000, 5_000]).unwrap(), vec![1, 0]);
+        assert_eq!(split_among_payees(u64::MAX, &[BPS_DENOMINATOR]).unwrap(), vec![u64::MAX]);
+    }
+
+    #[test]
+    fn payee_shares_must_add_up_to_100_percent() {
+        assert!(split_among_payees(100, &[5_000, 4_999]).is_err());
+        assert!(split_among_payees(100, &[]).is_err());
+    }
+
+    #[test]
+    fn pool_share_is_split_by_weight_without_loss() {
+        //30% of a 1 SOL ticket split 50/30/20 across three slots
+        let pool: u64 = bps_share(1_000_000_001, 3_000).unwrap();
+        let slots: Vec<u64> = split_among_payees(pool, &[5_000, 3_000, 2_000]).unwrap();
+        assert_eq!(slots, vec![150_000_000, 90_000_000, 60_000_000]);
+        assert_eq!(slots.iter().sum::<u64>(), pool);
+        assert_eq!(split_among_payees(bps_share(10, 3_000).unwrap(), &[5_000, 5_000]).unwrap(), vec![2, 1]);
+    }
+
+    #[test]
+    fn prize_shares_are_renormalised_over_filled_slots() {
+        assert_eq!(split_by_weights(1_000, &[5_000, 3_000, 2_000]).unwrap(), vec![500, 300, 200]);
+        //only two winners drawn, the 50/30 shares take the whole pool
+        assert_eq!(split_by_weights(1_000, &[5_000, 3_000]).unwrap(), vec![625, 375]);
+        assert_eq!(split_by_weights(10, &[1, 1, 1]).unwrap(), vec![4, 3, 3]);
+        assert_eq!(split_by_weights(u64::MAX, &[1, 1]).unwrap().iter().map(|&x| x as u128).sum::<u128>(), u64::MAX as u128);
+        assert!(split_by_weights(10, &[]).is_err());
+    }
+}
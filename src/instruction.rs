@@ -1,11 +1,11 @@
-use crate::{error::RaffleProgramError::InvalidInstruction, state::{ InitRaffle, RandomNumber, RewardFeeType, Rewards, Term}};
+use crate::{error::RaffleProgramError::InvalidInstruction, state::{ AuthorityProposal, FeeCurrency, FeeSchedule, FeeWithdrawal, GovernanceSettings, InitRaffle, InitSeries, JoinRaffle, ProposalData, RaffleExtension, RandomNumber, Roles, TermDelay, RewardFeeType, RewardType, Rewards, Term}};
 use borsh::BorshDeserialize;
 use solana_program::program_error::ProgramError;
 
 #[derive(Debug, PartialEq)]
 pub enum RaffleProgramInstruction {
     InitRaffle{init_raffle:InitRaffle},
-    JoinRaffle,
+    JoinRaffle{join_raffle:JoinRaffle},
     ChooseWinner{rng_call_limit:RandomNumber},
     PublishWinner,
     ClaimPrize,
@@ -14,15 +14,37 @@ pub enum RaffleProgramInstruction {
     InitTerm,
     InitConfig,
     SetConfig,
+    MigrateConfig,
+    MigrateRaffle,
     UpdateTerm{data:Term},
-    CollectFee,
-    CollectFeeInitializer,
+    CollectFee{data:FeeWithdrawal},
+    CollectFeeInitializer{data:FeeCurrency},
     InitFeeCollector,
     InitFeeType{data:RewardFeeType},
-    InitRewType{data:RewardFeeType},
-    CollectFeeToken,
+    InitRewType{data:RewardType},
+    SetFeeSchedule{data:FeeSchedule},
+    CollectFeeToken{data:FeeWithdrawal},
     AddTokenPool{rewards:Rewards},
     AddSolPool{rewards:Rewards},
+    InitGovernance{data:GovernanceSettings},
+    CreateProposal{data:ProposalData},
+    ApproveProposal,
+    ExecuteProposal,
+    InitRoles{data:Roles},
+    SetRoles{data:Roles},
+    Pause,
+    Unpause,
+    ProposeAuthority{data:AuthorityProposal},
+    AcceptAuthority,
+    CancelAuthority,
+    ApplyPendingTerm,
+    SetTermDelay{data:TermDelay},
+    WithdrawReferral,
+    ExtendRaffle{data:RaffleExtension},
+    InitSeries{data:InitSeries},
+    SpawnSeriesRound,
+    CloseSeries,
+    RolloverPool,
 }
 
 impl RaffleProgramInstruction {
@@ -33,7 +55,10 @@ impl RaffleProgramInstruction {
       0 => Self::InitRaffle{
         init_raffle:InitRaffle::try_from_slice(&rest)?
       },
-      1 => Self::JoinRaffle,
+      1 => Self::JoinRaffle{
+        //callers from before referrals send no data, they join without referrer, proof or attestation
+        join_raffle:if rest.is_empty() {JoinRaffle{referrer:[0; 32], proof:vec![], allowed_entries:0, attestation_expiry:0}} else {JoinRaffle::try_from_slice(rest)?}
+      },
       2 => Self::ChooseWinner{
         rng_call_limit:RandomNumber::try_from_slice(&rest)?
       },
@@ -43,26 +68,73 @@ impl RaffleProgramInstruction {
       6 => Self::InitTerm,
       7 => Self::InitConfig,
       8 => Self::SetConfig,
+      13 => Self::MigrateConfig,
+      14 => Self::MigrateRaffle,
       9 => Self::UpdateTerm{
         data:Term::try_from_slice(&rest)?
       },
-      10 => Self::CollectFee,
-      20 => Self::CollectFeeToken,
+      10 => Self::CollectFee{
+        data:FeeWithdrawal::try_from_slice(rest)?
+      },
+      11 => Self::ApplyPendingTerm,
+      12 => Self::SetTermDelay{
+        data:TermDelay::try_from_slice(rest)?
+      },
+      20 => Self::CollectFeeToken{
+        data:FeeWithdrawal::try_from_slice(rest)?
+      },
       35 => Self::InitFeeType{
         data:RewardFeeType::try_from_slice(&rest)?
       },
       36 => Self::InitRewType{
-        data:RewardFeeType::try_from_slice(&rest)?
+        data:RewardType::try_from_slice(&rest)?
+      },
+      37 => Self::SetFeeSchedule{
+        data:FeeSchedule::try_from_slice(rest)?
       },
       40 => Self::InitFeeCollector,
+      50 => Self::InitGovernance{
+        data:GovernanceSettings::try_from_slice(rest)?
+      },
+      51 => Self::CreateProposal{
+        data:ProposalData::try_from_slice(rest)?
+      },
+      52 => Self::ApproveProposal,
+      53 => Self::ExecuteProposal,
+      60 => Self::InitRoles{
+        data:Roles::try_from_slice(rest)?
+      },
+      61 => Self::SetRoles{
+        data:Roles::try_from_slice(rest)?
+      },
+      62 => Self::Pause,
+      63 => Self::Unpause,
+      70 => Self::ProposeAuthority{
+        data:AuthorityProposal::try_from_slice(rest)?
+      },
+      71 => Self::AcceptAuthority,
+      72 => Self::CancelAuthority,
+      80 => Self::WithdrawReferral,
       100 => Self::ClaimPrize,
-      200 => Self::CollectFeeInitializer,
+      200 => Self::CollectFeeInitializer{
+        //callers from before accepted fees send no data, they collect participation_fee_mint
+        data:if rest.is_empty() {FeeCurrency{fee_currency:0}} else {FeeCurrency::try_from_slice(rest)?}
+      },
       210 => Self::AddSolPool {
         rewards:Rewards::try_from_slice(&rest)?
       },
       220 => Self::AddTokenPool{
         rewards:Rewards::try_from_slice(&rest)?
       },
+      230 => Self::ExtendRaffle{
+        data:RaffleExtension::try_from_slice(rest)?
+      },
+      240 => Self::InitSeries{
+        data:InitSeries::try_from_slice(rest)?
+      },
+      241 => Self::SpawnSeriesRound,
+      242 => Self::CloseSeries,
+      250 => Self::RolloverPool,
 
       _ => return Err(InvalidInstruction.into()),
     })
@@ -1,5 +1,10 @@
+pub mod attestation;
 pub mod entrypoint;
 pub mod error;
+pub mod fees;
 pub mod instruction;
+pub mod merkle;
+pub mod nft;
+pub mod pricing;
 pub mod processor;
 pub mod state;
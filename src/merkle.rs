@@ -0,0 +1,107 @@
+use solana_program::keccak::hashv;
+
+//proofs longer than this would allow more than 2^24 wallets, reject them before hashing
+pub const MAX_PROOF_LEN: usize = 24;
+
+//Leaves and inner nodes use different prefixes so an inner node can never be passed off as a leaf.
+//Pairs are hashed in sorted order, so a proof does not need to carry left/right flags.
+pub fn leaf(wallet: &[u8;32], max_entries: u64) -> [u8;32] {
+    hashv(&[&[0], wallet, &max_entries.to_le_bytes()]).to_bytes()
+}
+
+pub fn hash_pair(a: &[u8;32], b: &[u8;32]) -> [u8;32] {
+    if a <= b {
+        hashv(&[&[1], a, b]).to_bytes()
+    }else{
+        hashv(&[&[1], b, a]).to_bytes()
+    }
+}
+
+pub fn verify(proof: &[[u8;32]], root: &[u8;32], leaf: [u8;32]) -> bool {
+
+    if proof.len() > MAX_PROOF_LEN {return false;}
+
+    let computed: [u8;32] = proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling));
+
+    &computed == root
+}
+
+//Off-chain helpers. A node without a sibling is carried up to the next level unchanged.
+pub fn build_root(leaves: &[[u8;32]]) -> [u8;32] {
+
+    if leaves.is_empty() {return [0; 32];}
+
+    let mut level: Vec<[u8;32]> = leaves.to_vec();
+
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+
+    level[0]
+}
+
+pub fn build_proof(leaves: &[[u8;32]], mut index: usize) -> Vec<[u8;32]> {
+
+    let mut proof: Vec<[u8;32]> = Vec::new();
+    let mut level: Vec<[u8;32]> = leaves.to_vec();
+
+    while level.len() > 1 {
+        let sibling: usize = index ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling]);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+
+    proof
+}
+
+fn next_level(level: &[[u8;32]]) -> Vec<[u8;32]> {
+    level.chunks(2).map(|pair| {
+        if pair.len() == 2 {hash_pair(&pair[0], &pair[1])} else {pair[0]}
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: u8) -> Vec<[u8;32]> {
+        (0..n).map(|i| leaf(&[i; 32], i as u64)).collect()
+    }
+
+    #[test]
+    fn every_leaf_has_a_valid_proof() {
+        for n in 1..10 {
+            let leaves: Vec<[u8;32]> = leaves(n);
+            let root: [u8;32] = build_root(&leaves);
+            for (i, l) in leaves.iter().enumerate() {
+                assert!(verify(&build_proof(&leaves, i), &root, *l));
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_entry_cap_is_rejected() {
+        let leaves: Vec<[u8;32]> = leaves(5);
+        let root: [u8;32] = build_root(&leaves);
+        let proof: Vec<[u8;32]> = build_proof(&leaves, 3);
+        assert!(!verify(&proof, &root, leaf(&[3; 32], 4)));
+        assert!(!verify(&proof, &root, leaf(&[4; 32], 3)));
+    }
+
+    #[test]
+    fn single_leaf_is_its_own_root() {
+        let leaves: Vec<[u8;32]> = leaves(1);
+        assert_eq!(build_root(&leaves), leaves[0]);
+        assert!(build_proof(&leaves, 0).is_empty());
+    }
+
+    #[test]
+    fn overlong_proof_is_rejected() {
+        let proof: Vec<[u8;32]> = vec![[0; 32]; MAX_PROOF_LEN + 1];
+        let root: [u8;32] = proof.iter().fold([0; 32], |node, sibling| hash_pair(&node, sibling));
+        assert!(!verify(&proof, &root, [0; 32]));
+    }
+}
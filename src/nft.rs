@@ -0,0 +1,91 @@
+use borsh::BorshDeserialize;
+use solana_program::{program_error::ProgramError, pubkey, pubkey::Pubkey};
+
+pub const METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &METADATA_PROGRAM_ID,
+    ).0
+}
+
+//Reads the Metaplex metadata account field by field up to the collection and returns
+//the collection key only when it is verified. Returns the mint the metadata belongs to as well.
+pub fn verified_collection(data: &[u8]) -> Result<([u8;32], Option<[u8;32]>), ProgramError> {
+
+    let mut data: &[u8] = data;
+
+    let key: u8 = u8::deserialize(&mut data)?;
+    if key != 4 {return Err(ProgramError::InvalidAccountData);}//MetadataV1
+
+    let _update_authority: [u8;32] = <[u8;32]>::deserialize(&mut data)?;
+    let mint: [u8;32] = <[u8;32]>::deserialize(&mut data)?;
+    let _name: String = String::deserialize(&mut data)?;
+    let _symbol: String = String::deserialize(&mut data)?;
+    let _uri: String = String::deserialize(&mut data)?;
+    let _seller_fee_basis_points: u16 = u16::deserialize(&mut data)?;
+
+    if u8::deserialize(&mut data)? == 1 {
+        let creators: u32 = u32::deserialize(&mut data)?;
+        for _ in 0..creators {
+            let _creator: ([u8;32], u8, u8) = <([u8;32], u8, u8)>::deserialize(&mut data)?;
+        }
+    }
+
+    let _primary_sale_happened: u8 = u8::deserialize(&mut data)?;
+    let _is_mutable: u8 = u8::deserialize(&mut data)?;
+    let _edition_nonce: Option<u8> = Option::<u8>::deserialize(&mut data)?;
+    let _token_standard: Option<u8> = Option::<u8>::deserialize(&mut data)?;
+
+    let collection: Option<(u8, [u8;32])> = Option::<(u8, [u8;32])>::deserialize(&mut data)?;
+
+    match collection {
+        Some((1, collection_key)) => Ok((mint, Some(collection_key))),
+        _ => Ok((mint, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    fn metadata(collection: Option<(u8, [u8;32])>) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        4u8.serialize(&mut data).unwrap();
+        [1u8; 32].serialize(&mut data).unwrap();
+        [2u8; 32].serialize(&mut data).unwrap();
+        "name\0\0".to_string().serialize(&mut data).unwrap();
+        "SYM".to_string().serialize(&mut data).unwrap();
+        "https://example.com".to_string().serialize(&mut data).unwrap();
+        500u16.serialize(&mut data).unwrap();
+        Some(vec![([3u8; 32], 1u8, 100u8)]).serialize(&mut data).unwrap();
+        1u8.serialize(&mut data).unwrap();
+        1u8.serialize(&mut data).unwrap();
+        Some(255u8).serialize(&mut data).unwrap();
+        None::<u8>.serialize(&mut data).unwrap();
+        collection.serialize(&mut data).unwrap();
+        //padding left by the metadata program after the fields we read
+        data.extend_from_slice(&[0; 64]);
+        data
+    }
+
+    #[test]
+    fn reads_verified_collection() {
+        assert_eq!(verified_collection(&metadata(Some((1, [9; 32])))).unwrap(), ([2; 32], Some([9; 32])));
+    }
+
+    #[test]
+    fn unverified_collection_is_ignored() {
+        assert_eq!(verified_collection(&metadata(Some((0, [9; 32])))).unwrap(), ([2; 32], None));
+        assert_eq!(verified_collection(&metadata(None)).unwrap(), ([2; 32], None));
+    }
+
+    #[test]
+    fn rejects_other_account_types() {
+        let mut data: Vec<u8> = metadata(Some((1, [9; 32])));
+        data[0] = 6;
+        assert!(verified_collection(&data).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+//Each step price applies from its time on, the base price applies before the first step.
+//Step times are checked to be increasing when the raffle is created.
+pub fn step_price(base_price: u64, times: &[u64], prices: &[u64], now: u64) -> u64 {
+
+    let mut price: u64 = base_price;
+
+    for (time, step) in times.iter().zip(prices) {
+        if now < *time {break;}
+        price = *step;
+    }
+
+    price
+}
+
+//Moves from start_price at start to end_price at end, prices can go up or down.
+//The change so far is rounded down, computed in u128 so large prices do not overflow.
+pub fn linear_price(start_price: u64, end_price: u64, start: u64, end: u64, now: u64) -> u64 {
+
+    if now <= start || end <= start {return start_price;}
+    if now >= end {return end_price;}
+
+    let elapsed: u128 = (now - start) as u128;
+    let duration: u128 = (end - start) as u128;
+
+    if end_price >= start_price {
+        let change: u128 = ((end_price - start_price) as u128) * elapsed / duration;
+        start_price + change as u64
+    }else{
+        let change: u128 = ((start_price - end_price) as u128) * elapsed / duration;
+        start_price - change as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_apply_from_their_time() {
+        let times: [u64; 2] = [100, 200];
+        let prices: [u64; 2] = [15, 20];
+        assert_eq!(step_price(10, &times, &prices, 99), 10);
+        assert_eq!(step_price(10, &times, &prices, 100), 15);
+        assert_eq!(step_price(10, &times, &prices, 199), 15);
+        assert_eq!(step_price(10, &times, &prices, 5_000), 20);
+        assert_eq!(step_price(10, &[], &[], 5_000), 10);
+    }
+
+    #[test]
+    fn linear_price_rises_and_falls() {
+        assert_eq!(linear_price(100, 200, 0, 100, 0), 100);
+        assert_eq!(linear_price(100, 200, 0, 100, 33), 133);
+        assert_eq!(linear_price(100, 200, 0, 100, 100), 200);
+        assert_eq!(linear_price(200, 100, 0, 100, 33), 167);
+        assert_eq!(linear_price(200, 100, 0, 100, 150), 100);
+    }
+
+    #[test]
+    fn linear_price_handles_large_prices() {
+        assert_eq!(linear_price(0, u64::MAX, 0, 2, 1), u64::MAX / 2);
+        assert_eq!(linear_price(u64::MAX, 0, 0, 2, 1), u64::MAX - u64::MAX / 2);
+        //an empty window keeps the start price
+        assert_eq!(linear_price(5, 9, 10, 10, 10), 5);
+    }
+}
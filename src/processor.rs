@@ -1,21 +1,32 @@
-use crate::{instruction::RaffleProgramInstruction, state::{ Config, FeeCollector, InitRaffle, Participant, Raffle, RaffleCounter, RandomNumber, RewardFeeType, Rewards, Term}};
+use crate::{attestation, fees::{bps_share, split_among_payees, split_by_weights, split_fee, BPS_DENOMINATOR}, instruction::RaffleProgramInstruction, merkle, nft, pricing, state::{ AcceptedFee, AuthorityProposal, AuthorityRotation, Config, ConfigTreasury, EntryCounter, FeeCollector, FeeCurrency, FeeLedger, FeeSchedule, FeeWithdrawal, Governance, GovernanceSettings, InitRaffle, InitSeries, JoinRaffle, LegacyRaffle, NftEntry, Participant, PendingTerm, Proposal, ProposalData, Raffle, RaffleCounter, RaffleExtension, RaffleSetup, RaffleSeries, RandomNumber, Referral, RewardFeeType, RewardType, Rewards, Roles, Term, TermDelay}};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
-    account_info::{next_account_info, AccountInfo}, entrypoint::ProgramResult, instruction::{AccountMeta, Instruction}, msg, program::{get_return_data, invoke, invoke_signed}, pubkey::Pubkey, rent::Rent, system_instruction, system_program, sysvar::{clock::Clock, Sysvar,}
+    account_info::{next_account_info, AccountInfo}, entrypoint::ProgramResult, instruction::{AccountMeta, Instruction}, msg, program::{get_return_data, invoke, invoke_signed}, ed25519_program, program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_instruction, system_program, sysvar::{clock::Clock, instructions::{load_current_index_checked, load_instruction_at_checked}, Sysvar,}
 };
 
 use solana_program::program_pack::Pack;
-use spl_token::state::{Account, Mint};
+use spl_token::state::Account;
+use spl_token_2022::extension::StateWithExtensions;
 
 
 use crate::error::RaffleProgramError::{InvalidCounter, ArithmeticError, InvalidInitializer, WritableAccount,InvalidMint,
      ParticipantNotSigner, MaxNumberReached, InvalidWinner, InvalidFee,  InvalidRaffleState,InvalidRewardType,
      InitializerNotSigner, InvalidWinnerPDA,InvalidRaffleNo, InvalidParticipantPDA,InvalidFeeType,RNGProgramError,
     InvalidConfig, NotSignerAuth,  InvalidAuth,  InvalidRaffle, InvalidTerms, InvalidRaffleTime,InvalidWinnerNumber,
+    GovernanceEnabled, InvalidGovernance, InvalidProposal, NotGovernanceMember, ThresholdNotReached, InvalidRoles, ProgramPaused,
+    InvalidAuthoritySlot, InvalidRotation, InvalidPendingTerm, TermNotEffective,
+    InvalidReferral, InvalidRevenueSplit, FeeTypeDisabled, InvalidParticipationFee,
+    InvalidFeeLedger, InvalidTreasury, InsufficientFees, NotAllowlisted, EntryLimitReached, InvalidTokenAccount, InvalidEntryCounter, InvalidCollection, NftAlreadyUsed, InvalidRequirement, RequirementNotMet, InvalidParticipantLimits, RaffleNotStarted, InvalidExtension, InvalidSeries, RoundNotSettled, SeriesNotFunded, PrizeExpired, InvalidRollover, InvalidPoolSplit, InvalidPrizeShares, InvalidConsolation, InvalidPriceSchedule, InvalidAttestation, AttestationExpired, InvalidAcceptedFees,
     };
 
 use spl_associated_token_account::instruction::create_associated_token_account;
 
+//term changes can never apply faster than this, whatever min_delay is set to
+const MIN_TERM_DELAY: u64 = 86_400;
+
+//how many times an initializer can push raffle_time back
+const MAX_EXTENSIONS: u8 = 3;
+
 
 pub struct Processor;
 impl Processor {
@@ -30,8 +41,8 @@ impl Processor {
             RaffleProgramInstruction::InitRaffle {init_raffle}=> {
                 Self::init_raffle(accounts, program_id, init_raffle)
             },
-            RaffleProgramInstruction::JoinRaffle => {
-                Self::join_raffle(accounts,program_id)
+            RaffleProgramInstruction::JoinRaffle {join_raffle} => {
+                Self::join_raffle(accounts,program_id,join_raffle)
             },
             RaffleProgramInstruction::ChooseWinner {rng_call_limit}=> {
                 Self::choose_winner(accounts, program_id, rng_call_limit)
@@ -40,7 +51,7 @@ impl Processor {
                 Self::publish_winner(accounts, program_id)
             },
             RaffleProgramInstruction::ClosePDA => {
-                Self::close_participant_pda(accounts)
+                Self::close_participant_pda(accounts, program_id)
             },
             RaffleProgramInstruction::InitTerm => {
                 Self::init_term_account(accounts, program_id)
@@ -51,20 +62,26 @@ impl Processor {
             RaffleProgramInstruction::InitCounter => {
                 Self::init_raffle_counter(accounts, program_id)
             },
+            RaffleProgramInstruction::MigrateConfig => {
+                Self::migrate_config(accounts, program_id)
+            },
+            RaffleProgramInstruction::MigrateRaffle => {
+                Self::migrate_raffle(accounts, program_id)
+            },
             RaffleProgramInstruction::SetConfig => {
                 Self::set_config(accounts, program_id)
             },
             RaffleProgramInstruction::UpdateTerm {data}=> {
                 Self::update_terms(accounts, program_id, data)
             },
-            RaffleProgramInstruction::CollectFee => {
-                Self::collect_fee(accounts, program_id)
+            RaffleProgramInstruction::CollectFee {data}=> {
+                Self::collect_fee(accounts, program_id, data)
             },
             RaffleProgramInstruction::ClaimPrize => {
                 Self::claim_prize(accounts, program_id)
             },
-            RaffleProgramInstruction::CollectFeeInitializer => {
-                Self::collect_fee_initializer(accounts, program_id)
+            RaffleProgramInstruction::CollectFeeInitializer {data}=> {
+                Self::collect_fee_initializer(accounts, program_id, data)
             },
             RaffleProgramInstruction::InitFeeType {data}=> {
                 Self::init_fee_type_account(accounts, program_id, data)
@@ -72,8 +89,11 @@ impl Processor {
             RaffleProgramInstruction::InitRewType {data}=> {
                 Self::init_reward_type_account(accounts, program_id, data)
             },
-            RaffleProgramInstruction::CollectFeeToken => {
-                Self::collect_fee_token(accounts, program_id)
+            RaffleProgramInstruction::SetFeeSchedule {data}=> {
+                Self::set_fee_schedule(accounts, program_id, data)
+            },
+            RaffleProgramInstruction::CollectFeeToken {data}=> {
+                Self::collect_fee_token(accounts, program_id, data)
             },
             RaffleProgramInstruction::InitFeeCollector => {
                 Self::init_fee_collector_account(accounts, program_id)
@@ -84,6 +104,63 @@ impl Processor {
             RaffleProgramInstruction::AddTokenPool { rewards } => {
                 Self::add_tokens_to_the_reward_pool(accounts, program_id,rewards)
             },
+            RaffleProgramInstruction::InitGovernance { data } => {
+                Self::init_governance(accounts, program_id, data)
+            },
+            RaffleProgramInstruction::CreateProposal { data } => {
+                Self::create_proposal(accounts, program_id, data)
+            },
+            RaffleProgramInstruction::ApproveProposal => {
+                Self::approve_proposal(accounts, program_id)
+            },
+            RaffleProgramInstruction::ExecuteProposal => {
+                Self::execute_proposal(accounts, program_id)
+            },
+            RaffleProgramInstruction::InitRoles { data } => {
+                Self::init_roles(accounts, program_id, data)
+            },
+            RaffleProgramInstruction::SetRoles { data } => {
+                Self::set_roles(accounts, program_id, data)
+            },
+            RaffleProgramInstruction::Pause => {
+                Self::set_pause(accounts, program_id, 1)
+            },
+            RaffleProgramInstruction::Unpause => {
+                Self::set_pause(accounts, program_id, 0)
+            },
+            RaffleProgramInstruction::ProposeAuthority { data } => {
+                Self::propose_authority(accounts, program_id, data)
+            },
+            RaffleProgramInstruction::AcceptAuthority => {
+                Self::accept_authority(accounts, program_id)
+            },
+            RaffleProgramInstruction::CancelAuthority => {
+                Self::cancel_authority(accounts, program_id)
+            },
+            RaffleProgramInstruction::ApplyPendingTerm => {
+                Self::apply_pending_term(accounts, program_id)
+            },
+            RaffleProgramInstruction::SetTermDelay { data } => {
+                Self::set_term_delay(accounts, program_id, data)
+            },
+            RaffleProgramInstruction::WithdrawReferral => {
+                Self::withdraw_referral(accounts, program_id)
+            },
+            RaffleProgramInstruction::ExtendRaffle { data } => {
+                Self::extend_raffle(accounts, program_id, data)
+            },
+            RaffleProgramInstruction::InitSeries { data } => {
+                Self::init_series(accounts, program_id, data)
+            },
+            RaffleProgramInstruction::SpawnSeriesRound => {
+                Self::spawn_series_round(accounts, program_id)
+            },
+            RaffleProgramInstruction::CloseSeries => {
+                Self::close_series(accounts, program_id)
+            },
+            RaffleProgramInstruction::RolloverPool => {
+                Self::rollover_pool(accounts, program_id)
+            },
 
         }
     }
@@ -110,13 +187,17 @@ impl Processor {
        let fee_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
        let fee_mint_token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
        let sysvar: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+       let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-
+       Self::check_not_paused(config_account, program_id)?;
 
        let mut counter: RaffleCounter = RaffleCounter::try_from_slice(&counter_account.data.borrow())?;
-       let terms: Term = Term::try_from_slice(&term_account.data.borrow())?;
-       let reward_type: RewardFeeType = RewardFeeType::try_from_slice(&reward_type_pda.data.borrow())?;
-       let fee_type: RewardFeeType = RewardFeeType::try_from_slice(&fee_type_pda.data.borrow())?;
+       let terms: Term = Self::get_term(term_account, program_id)?;
+       let reward_type: RewardFeeType = Self::get_reward_fee_type(reward_type_pda)?;
+       let fee_type: RewardFeeType = Self::get_reward_fee_type(fee_type_pda)?;
+
+       //accepted currencies come right after the fixed accounts
+       let (accepted_fees, accepted_fee_accounts) = Self::read_accepted_fees(accounts_iter, &init_raffle, &terms, &fee_type, program_id)?;
 
        if reward_type.initialized != 2 {return Err(InvalidTerms.into());}
        if fee_type.initialized != 3 {return Err(InvalidTerms.into());}
@@ -133,10 +214,22 @@ impl Processor {
        msg!("{}",participation_fee_total);
        }
 
-       Self::check_participation_fee_type(program_id,&fee_type,fee_type_pda.key)?;
+       Self::check_price_schedule(program_id,&fee_type,fee_type_pda.key,&init_raffle)?;
        msg!("5");
 
-       Self::check_and_write_raffle_data(&init_raffle,&terms,reward_type.mint,fee_type.mint,reward_type.decimals,fee_type.decimals,counter.number_of_raffles,raffle_pda, initializer, program_id)?;
+       let fee_bps: u64 = if fee_type.has_fee_bps == 1 {fee_type.fee_bps} else {terms.fee_bps};
+
+       let setup: RaffleSetup = RaffleSetup {
+           raffle_no: counter.number_of_raffles,
+           fee_bps,
+           reward_mint: reward_type.mint,
+           reward_decimals: reward_type.decimals,
+           participation_fee_mint: fee_type.mint,
+           participation_fee_decimals: fee_type.decimals,
+           accepted_fees,
+       };
+
+       Self::check_and_write_raffle_data(&init_raffle,&terms,setup,raffle_pda, initializer, initializer.key, program_id)?;
 
        msg!("reward_type {}",init_raffle.reward_type);
        msg!("participation_fee_type {}",init_raffle.participation_fee_type);
@@ -154,6 +247,12 @@ impl Processor {
        }
        msg!("8");
 
+       for [accepted_mint, raffle_accepted_ata, accepted_token_program] in accepted_fee_accounts {
+        if accepted_mint.key.to_bytes() != reward_type.mint {
+            Self::create_ata(initializer,raffle_pda,raffle_accepted_ata,accepted_mint,accepted_token_program,sysvar)?;
+        }
+       }
+
 
        if reward_type.no == 1 {
 
@@ -183,6 +282,22 @@ impl Processor {
 
        }
 
+       //consolation escrow is held in the participation fee mint, next to the fees
+       if init_raffle.consolation_funding != 0 {
+
+            if fee_type.no == 1 {
+
+                invoke(&system_instruction::transfer(initializer.key, raffle_pda.key, init_raffle.consolation_funding),
+                    &[initializer.clone(),raffle_pda.clone()])?;
+
+            }else{
+
+                let initializer_fee_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+                Self::transfer_tokens_to_raffle_pda(fee_mint,raffle_fee_ata,initializer,initializer_fee_ata,fee_mint_token_program,fee_type.decimals,init_raffle.consolation_funding)?;
+            }
+       }
+
 
         counter.serialize(&mut &mut counter_account.data.borrow_mut()[..])?;
 
@@ -190,7 +305,7 @@ impl Processor {
     }
 
     fn join_raffle(
-        accounts: &[AccountInfo],program_id: &Pubkey,
+        accounts: &[AccountInfo],program_id: &Pubkey,join_raffle:JoinRaffle
     ) -> ProgramResult{
 
 
@@ -200,18 +315,26 @@ impl Processor {
         let raffle_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
         let participant_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
         let participation_fee_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
         if raffle_pda.owner != program_id {return Err(InvalidRaffle.into());}
         if !participant.is_signer {return Err(ParticipantNotSigner.into());}
 
+        Self::check_not_paused(config_account, program_id)?;
+
         let mut raffle: Raffle = Raffle::try_from_slice(&raffle_pda.data.borrow())?;
 
-        if participation_fee_mint.key.to_bytes() != raffle.participation_fee_mint { return Err(InvalidFeeType.into());}
+        //the participant picks the currency by passing its mint
+        let fee_currency: u8 = if participation_fee_mint.key.to_bytes() == raffle.participation_fee_mint {0} else {
+            let position: usize = raffle.accepted_fees.iter().position(|accepted| accepted.mint == participation_fee_mint.key.to_bytes()).ok_or(InvalidFeeType)?;
+            (position + 1) as u8
+        };
+        let (fee_type_no, fee_mint, fee_decimals): (u64, [u8;32], u8) = Self::fee_currency_of(&raffle, fee_currency)?;
 
         if raffle.raffle_state != 1 {return Err(InvalidRaffleState.into());}
 
         if raffle.is_unlimited_participant_allowed != 1{
-            if raffle.participants_required <= raffle.current_number_of_participants {return Err(MaxNumberReached.into());}
+            if raffle.max_participants <= raffle.current_number_of_participants {return Err(MaxNumberReached.into());}
         }
 
         let clock: Clock= Clock::get()?;
@@ -220,6 +343,12 @@ impl Processor {
 
         //user cant join raffle after raffle time is passed
         if current_time > raffle.raffle_time {return Err(InvalidRaffleTime.into())}
+        if current_time < raffle.start_time {return Err(RaffleNotStarted.into())}
+
+        if raffle.allowlist_root != [0; 32] {
+            let leaf: [u8;32] = merkle::leaf(&participant.key.to_bytes(), join_raffle.allowed_entries);
+            if !merkle::verify(&join_raffle.proof, &raffle.allowlist_root, leaf) {return Err(NotAllowlisted.into());}
+        }
 
         raffle.current_number_of_participants = raffle.current_number_of_participants.checked_add(1).ok_or(ArithmeticError)?;
         msg!("3");
@@ -227,13 +356,38 @@ impl Processor {
 
         Self::init_participant_pda(participant, participant_pda, raffle.multiple_participation_allowed, raffle.raffle_no, raffle.current_number_of_participants, program_id)?;
 
-        let fee:u64 = raffle.participation_fee;
-        if raffle.participation_fee_type == 1 {
+        let is_referred: bool = join_raffle.referrer != [0; 32] && raffle.referral_bps != 0;
+
+        if join_raffle.referrer == participant.key.to_bytes() {return Err(InvalidReferral.into());}
+
+        let price: u64 = if fee_currency != 0 {raffle.accepted_fees[fee_currency as usize - 1].price} else { match raffle.pricing_mode {
+            1 => pricing::step_price(raffle.participation_fee, &raffle.price_step_times, &raffle.price_step_prices, current_time),
+            2 => {
+                let curve_start: u64 = if raffle.start_time != 0 {raffle.start_time} else {raffle.created_at};
+                pricing::linear_price(raffle.participation_fee, raffle.end_price, curve_start, raffle.raffle_time, current_time)
+            },
+            _ => raffle.participation_fee,
+        }};
+
+        let mut referral_share: u64 = 0;
+        if is_referred {
+            referral_share = bps_share(price, raffle.referral_bps)?;
+        }
+
+        //the referral share goes straight to the referral pda, the raffle only receives the rest.
+        //a raffle that may refund keeps the share until it is published, the refund is then the full price
+        let fee:u64 = price.checked_sub(referral_share).ok_or(ArithmeticError)?;
+        let is_referral_held: bool = raffle.refund_below_min == 1;
+        let paid_to_raffle: u64 = if is_referral_held {price} else {fee};
+        let paid_to_referral: u64 = if is_referral_held {0} else {referral_share};
+        let mut fee_token_accounts: Option<(&AccountInfo<'_>, &AccountInfo<'_>)> = None;
+
+        if fee_type_no == 1 {
 
             invoke(&system_instruction::transfer(
                 participant.key,
                 raffle_pda.key, 
-                fee), 
+                paid_to_raffle), 
                 &[participant.clone(),raffle_pda.clone()])?;
 
 
@@ -249,12 +403,14 @@ impl Processor {
            Self::check_mint_and_owner(participation_fee_mint.key, participant.key, participant_ata)?;
            Self::check_mint_and_owner(participation_fee_mint.key, raffle_pda.key, raffle_ata)?;
 
-           msg!("participation_fee_type{}",raffle.participation_fee_type);
-           msg!("participation_fee_decimals{}",raffle.participation_fee_decimals);
-           msg!("participation_fee_mint {}",Pubkey::new_from_array(raffle.participation_fee_mint).to_string());
+           msg!("participation_fee_type{}",fee_type_no);
+           msg!("participation_fee_decimals{}",fee_decimals);
+           msg!("participation_fee_mint {}",Pubkey::new_from_array(fee_mint).to_string());
 
            Self::transfer_tokens_to_raffle_pda(participation_fee_mint, raffle_ata, participant, participant_ata, 
-            token_program, raffle.participation_fee_decimals, fee)?;
+            token_program, fee_decimals, paid_to_raffle)?;
+
+           fee_token_accounts = Some((participant_ata, token_program));
         }
 
 
@@ -276,17 +432,121 @@ impl Processor {
         
             Self::transfer_tokens_to_raffle_pda(requirement_mint, raffle_requirement_ata, participant, participant_requirement_ata, req_mint_token_program, raffle.required_token_decimals, raffle.requirement_amount_token)?;
         
+        }else if raffle.requirement_to_participate == 2 {
+
+            //requirement_mint holds the collection mint, any verified member of it is accepted
+            let nft_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+            let participant_nft_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+            let nft_metadata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+            Self::check_collection_nft(participant, nft_mint, participant_nft_ata, nft_metadata, raffle.requirement_mint)?;
+
+            if raffle.one_entry_per_nft == 1 {
+
+                let nft_entry: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+                Self::record_nft_entry(participant, nft_entry, raffle.raffle_no, nft_mint.key, program_id)?;
+            }
+        }else if raffle.requirement_to_participate == 3 {
+
+            //only the balance is checked, nothing is escrowed
+            let participant_requirement_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+            Self::check_requirement_balance(&raffle, participant.key, participant_requirement_ata)?;
+        }
+
+
+        if is_referred {
+
+            let referral_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+            //a held share is accrued when claim_prize releases it, the accounts are still created now
+            Self::accrue_referral(participant, referral_pda, join_raffle.referrer, fee_mint, paid_to_referral, program_id)?;
+
+            if fee_type_no == 1 {
+
+                invoke(&system_instruction::transfer(
+                    participant.key,
+                    referral_pda.key,
+                    paid_to_referral),
+                    &[participant.clone(),referral_pda.clone()])?;
+
+            }else{
+
+                let referral_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+                let sysvar: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+                let (participant_ata, token_program) = fee_token_accounts.ok_or(InvalidReferral)?;
+
+                if referral_ata.owner != &spl_token::id() && referral_ata.owner != &spl_token_2022::id() {
+                    Self::create_ata(participant, referral_pda, referral_ata, participation_fee_mint, token_program, sysvar)?;
+                }
+
+                Self::check_mint_and_owner(participation_fee_mint.key, referral_pda.key, referral_ata)?;
+
+                Self::transfer_tokens_to_raffle_pda(participation_fee_mint, referral_ata, participant, participant_ata,
+                    token_program, fee_decimals, paid_to_referral)?;
+            }
+        }
+
+
+        //the raffle wide cap and the allowlist cap share one counter, the lower one applies
+        let mut max_entries: u64 = raffle.max_entries_per_wallet;
+        if raffle.allowlist_root != [0; 32] && join_raffle.allowed_entries != 0
+            && (max_entries == 0 || join_raffle.allowed_entries < max_entries) {
+            max_entries = join_raffle.allowed_entries;
+        }
+
+        if max_entries != 0 {
+
+            let entry_counter: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+            Self::count_entry(participant, entry_counter, raffle.raffle_no, max_entries, program_id)?;
+        }
+
+        if raffle.attester != [0; 32] {
+
+            let instructions_sysvar: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+            Self::check_attestation(&raffle, participant.key, join_raffle.attestation_expiry, current_time, instructions_sysvar)?;
         }
 
 
         if raffle.is_increasing_pool == 1 {
 
-            for i in 0..raffle.rewards.len() {
-                raffle.rewards[i] = raffle.rewards[i].checked_add(raffle.transfer_fee_to_pool[i]).ok_or(ArithmeticError)?;
+            //percentage mode splits a share of the fee by weight, rounding dust goes to the first slot
+            let pool_amounts: Vec<u64> = if raffle.pool_bps != 0 {
+                split_among_payees(bps_share(price, raffle.pool_bps)?, &raffle.pool_weights_bps)?
+            }else{
+                raffle.transfer_fee_to_pool.clone()
+            };
+
+            for (reward, amount) in raffle.rewards.iter_mut().zip(pool_amounts.iter()) {
+                *reward = reward.checked_add(*amount).ok_or(ArithmeticError)?;
+                raffle.fees_to_pool = raffle.fees_to_pool.checked_add(*amount).ok_or(ArithmeticError)?;
             }
 
         }
 
+        //other currencies can not feed the pool or consolations, their fees are only settled at collection
+        if fee_currency == 0 {
+            raffle.fees_received = raffle.fees_received.checked_add(fee).ok_or(ArithmeticError)?;
+        }else{
+            let accepted: &mut AcceptedFee = &mut raffle.accepted_fees[fee_currency as usize - 1];
+            accepted.fees_received = accepted.fees_received.checked_add(fee).ok_or(ArithmeticError)?;
+        }
+
+        if raffle.consolation_type != 0 {
+
+            //every entry is reserved until the winners are published
+            let consolation: u64 = Self::consolation_for(&raffle, price)?;
+            raffle.consolation_reserved = raffle.consolation_reserved.checked_add(consolation).ok_or(ArithmeticError)?;
+
+            let available: u64 = raffle.fees_received.checked_add(raffle.consolation_escrow).ok_or(ArithmeticError)?
+                .checked_sub(raffle.fees_to_pool).ok_or(ArithmeticError)?;
+            if raffle.consolation_reserved > available {return Err(InvalidConsolation.into());}
+        }
+
 
         msg!("6");
         let participant: Participant = Participant{
@@ -296,6 +556,10 @@ impl Processor {
             entitled: 0,
             prize_claimed: 0,
             index_in_winners: 0,
+            fee_paid: price,
+            fee_currency,
+            referrer: if is_referral_held && is_referred {join_raffle.referrer} else {[0; 32]},
+            referral_share: if is_referral_held {referral_share} else {0},
         };
 
         msg!("7");
@@ -320,41 +584,33 @@ impl Processor {
         let fee_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
         let rng_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
         let system_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-        let config: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
 
         let mut raffle: Raffle = Raffle::try_from_slice(&raffle_pda.data.borrow())?;
-        let  config: Config = Config::try_from_slice(&config.data.borrow())?;
+        let roles: Roles = Self::get_roles(roles_account, program_id)?;
 
         if raffle_pda.owner != program_id {return Err(InvalidRaffle.into());}
         if !authority.is_signer {return Err(NotSignerAuth.into());}
 
-        Self::check_authority(authority.key, config)?;
+        Self::check_role(authority.key, &roles.drawers)?;
 
 
         let clock: Clock= Clock::get()?;
         let current_time: u64 = clock.unix_timestamp as u64;
 {
         msg!("current_number_of_participants = {}",raffle.current_number_of_participants);
-        msg!("participants_required = {}",raffle.participants_required);
+        msg!("max_participants = {}",raffle.max_participants);
         msg!("current_winner_count = {}",raffle.current_winner_count);
         msg!("winner_count = {}",raffle.winner_count);
         msg!("raffle_state = {}",raffle.raffle_state);
 }
         if raffle.raffle_state != 1{return Err(InvalidRaffleState.into());}
 
-        if raffle.is_unlimited_participant_allowed == 1 {
-            msg!("2");
- 
-         //if current time is bigger than raffle time it is raffle time
-           //if current_time < raffle.raffle_time {return Err(InvalidRaffleState.into());}
-        }else{
-            msg!("3");
+        //before raffle_time a raffle can only be drawn once it is full
+        let is_full: bool = raffle.is_unlimited_participant_allowed != 1 && raffle.current_number_of_participants == raffle.max_participants;
 
-           if raffle.current_number_of_participants != raffle.participants_required && current_time < raffle.raffle_time {
-        msg!("4");
-        return Err(InvalidRaffleState.into());}
-        }
+        if !is_full && current_time < raffle.raffle_time {return Err(InvalidRaffleState.into());}
 
 
         let mut attempts:u64 = 0;
@@ -362,19 +618,52 @@ impl Processor {
         let mut winners: Vec<u64> = raffle.winners.clone();
         winners.retain(|&x| x != 0);
 
-        if raffle.current_number_of_participants == 0 {
+        if raffle.current_number_of_participants == 0 || raffle.current_number_of_participants < raffle.min_participants {
 
             msg!("abort raffle");
 
+        //for SOL rewards initializer_ata is the initializer wallet
         let initializer_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
         let raffle_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
         let reward_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
         let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-        let total_rewards: u64 = raffle.rewards.iter().sum();
+        let mut total_rewards: u64 = raffle.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+
+            if raffle.refund_below_min == 1 && raffle.current_number_of_participants != 0 {
+
+                //pool increments came out of participation fees, those are refunded to the participants instead
+                total_rewards = total_rewards.checked_sub(raffle.fees_to_pool).ok_or(ArithmeticError)?;
+
+                raffle.raffle_state = 4;
+
+            }else{
+
+                raffle.raffle_state = 3;
+                raffle.published_at = current_time;
+            }
+
+            if raffle.rollover == 1 {
+
+                //stays in the raffle until rollover_pool moves it to the next raffle
+                raffle.rollover_amount = total_rewards;
 
-            Self::abort_raffle(initializer_ata,raffle_pda,raffle_ata,reward_mint,token_program,total_rewards,raffle.raffle_no,raffle.reward_decimals,raffle.bump)?;
-            raffle.raffle_state = 3;
+            }else if raffle.reward_mint == system_program::ID.to_bytes() {
+
+                if initializer_ata.key.to_bytes() != raffle.initializer {return Err(InvalidInitializer.into());}
+
+                **raffle_pda.try_borrow_mut_lamports()? -= total_rewards;
+                **initializer_ata.try_borrow_mut_lamports()? += total_rewards;
+
+            }else{
+
+                Self::check_mint_and_owner(reward_mint.key, &Pubkey::new_from_array(raffle.initializer), initializer_ata)?;
+
+                Self::abort_raffle(initializer_ata,raffle_pda,raffle_ata,reward_mint,token_program,total_rewards,raffle.raffle_no,raffle.reward_decimals,raffle.bump)?;
+            }
+
+            //rewards are back with the initializer or set aside for the rollover, what is left in the raffle are the fees
+            raffle.rewards = vec![0; raffle.rewards.len()];
 
         }else if raffle.current_number_of_participants == 1  {
 
@@ -383,6 +672,8 @@ impl Processor {
             raffle.raffle_state = 2;
             raffle.current_winner_count = 1;
 
+            Self::freeze_prize_shares(&mut raffle)?;
+
         }else{
             let count:u64;
             if raffle.current_number_of_participants < raffle.winner_count {
@@ -415,6 +706,8 @@ impl Processor {
 
             if count == raffle.current_winner_count {
                 raffle.raffle_state = 2;
+
+                Self::freeze_prize_shares(&mut raffle)?;
             }
         }
         let n: usize = raffle.winner_count as usize;
@@ -453,10 +746,10 @@ impl Processor {
 
             let winner_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-            let mut participant: Participant = Participant::try_from_slice(&winner_pda.data.borrow())?;
-
             if winner_pda.owner != program_id {return Err(InvalidWinnerPDA.into());}
 
+            let mut participant: Participant = Self::get_participant(winner_pda)?;
+
             if raffle.raffle_no != participant.raffle_no{return Err(InvalidRaffleNo.into());}
             msg!("raffle no match");
 
@@ -472,7 +765,10 @@ impl Processor {
 
             raffle.number_of_entitled_winners = raffle.number_of_entitled_winners.checked_add(1).ok_or(ArithmeticError)?;
 
-            participant.serialize(&mut &mut winner_pda.data.borrow_mut()[..])?;
+            let consolation: u64 = Self::consolation_for(&raffle, participant.fee_paid)?;
+            raffle.consolation_reserved = raffle.consolation_reserved.checked_sub(consolation).ok_or(ArithmeticError)?;
+
+            Self::write_participant(&participant, winner_pda)?;
 
         }
 
@@ -490,6 +786,11 @@ impl Processor {
 
         }
 
+        if raffle.raffle_state == 3 {
+            let clock: Clock = Clock::get()?;
+            raffle.published_at = clock.unix_timestamp as u64;
+        }
+
 
         raffle.serialize(&mut &mut raffle_account.data.borrow_mut()[..])?;
 
@@ -513,15 +814,16 @@ impl Processor {
         let sysvar: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
 
-        let raffle: Raffle = Raffle::try_from_slice(&raffle_account.data.borrow())?;
-        let mut participant: Participant = Participant::try_from_slice(&winner_pda.data.borrow())?;
+        let mut raffle: Raffle = Raffle::try_from_slice(&raffle_account.data.borrow())?;
 
 
         if raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
         if winner_pda.owner != program_id {return Err(InvalidWinnerPDA.into());}
+
+        let mut participant: Participant = Self::get_participant(winner_pda)?;
         if raffle.raffle_no != participant.raffle_no{return Err(InvalidRaffleNo.into());}
         if winner_address.key.to_bytes() != participant.particpant_address {return Err(InvalidWinner.into());}
-        if raffle.raffle_state != 3{return Err(InvalidRaffleState.into());}
+        if raffle.raffle_state != 3 && raffle.raffle_state != 4{return Err(InvalidRaffleState.into());}
         if participant.prize_claimed != 0 {return Err(InvalidParticipantPDA.into());}
 
 
@@ -541,11 +843,96 @@ impl Processor {
                           req_mint_token_program, raffle.required_token_decimals, raffle.requirement_amount_token,raffle.raffle_no,     raffle.bump)?;
         }
 
+        if raffle.requirement_to_participate == 3 && raffle.recheck_requirement_at_claim == 1 && participant.entitled == 1 {
+
+            let participant_req_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+            Self::check_requirement_balance(&raffle, winner_address.key, participant_req_ata)?;
+        }
+
+        //refunds go back in the currency the participant paid, consolations only exist with a single currency
+        let mut fee_payout: u64 = 0;
+
+        if raffle.raffle_state == 4 {
+            fee_payout = participant.fee_paid;
+        }else if participant.entitled == 0 && raffle.consolation_type != 0 {
+            fee_payout = Self::consolation_for(&raffle, participant.fee_paid)?;
+        }
+
+        if fee_payout != 0 {
+
+            let (fee_type_no, fee_mint_bytes, fee_decimals): (u64, [u8;32], u8) = Self::fee_currency_of(&raffle, participant.fee_currency)?;
+
+            if fee_type_no == 1 {
+
+                **raffle_account.try_borrow_mut_lamports()? -= fee_payout;
+                **winner_address.try_borrow_mut_lamports()? += fee_payout;
+
+            }else{
+
+                let raffle_fee_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+                let participant_fee_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+                let fee_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+                let fee_token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+                if fee_mint_bytes != fee_mint.key.to_bytes() {return Err(InvalidMint.into());}
+
+                Self::check_mint_and_owner(fee_mint.key, winner_address.key, participant_fee_ata)?;
+
+                Self::transfer_tokens_from_raffle_pda(fee_mint, raffle_fee_ata, raffle_account, participant_fee_ata,
+                    fee_token_program, fee_decimals, fee_payout, raffle.raffle_no, raffle.bump)?;
+            }
+        }
+
+        //the raffle was published, the referral share it held is released to the referrer
+        if raffle.raffle_state == 3 && participant.referral_share != 0 {
+
+            let referral_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+            let (fee_type_no, fee_mint_bytes, fee_decimals): (u64, [u8;32], u8) = Self::fee_currency_of(&raffle, participant.fee_currency)?;
+
+            if referral_pda.owner != program_id {return Err(InvalidReferral.into());}
+
+            Self::accrue_referral(winner_address, referral_pda, participant.referrer, fee_mint_bytes, participant.referral_share, program_id)?;
+
+            if fee_type_no == 1 {
+
+                **raffle_account.try_borrow_mut_lamports()? -= participant.referral_share;
+                **referral_pda.try_borrow_mut_lamports()? += participant.referral_share;
+
+            }else{
+
+                let raffle_fee_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+                let referral_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+                let fee_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+                let fee_token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+                if fee_mint_bytes != fee_mint.key.to_bytes() {return Err(InvalidMint.into());}
+
+                Self::check_mint_and_owner(fee_mint.key, referral_pda.key, referral_ata)?;
+
+                Self::transfer_tokens_from_raffle_pda(fee_mint, raffle_fee_ata, raffle_account, referral_ata,
+                    fee_token_program, fee_decimals, participant.referral_share, raffle.raffle_no, raffle.bump)?;
+            }
+
+            participant.referral_share = 0;
+        }
+
         if participant.entitled == 1 {
                 
             if reward_mint.key.to_bytes() != raffle.reward_mint {return Err(InvalidWinner.into());}
+
+            if raffle.rolled_over == 1 {return Err(PrizeExpired.into());}
+            if raffle.claim_period != 0 {
+                let clock: Clock = Clock::get()?;
+                let claim_deadline: u64 = raffle.published_at.checked_add(raffle.claim_period).ok_or(ArithmeticError)?;
+                if clock.unix_timestamp as u64 > claim_deadline {return Err(PrizeExpired.into());}
+            }
+
             let index = raffle.winners.iter().position(|&x| x == participant.particpant_no).unwrap();
             let prize_amount:u64 = raffle.rewards[index];
+
+            raffle.rewards_claimed = raffle.rewards_claimed.checked_add(prize_amount).ok_or(ArithmeticError)?;
      
 
             if raffle.reward_mint == system_program::ID.to_bytes() {
@@ -561,11 +948,6 @@ impl Processor {
                     Self::create_ata(winner_address, winner_address, winner_ata, reward_mint, token_program_id, sysvar)?;
                    
                 }else{
-                
-                    let ata_unpacked: Account  = Account::unpack_from_slice(&winner_ata.data.borrow())?;
-        
-                    if reward_mint.key != &ata_unpacked.mint {panic!()}
-                    if winner_address.key != &ata_unpacked.owner {panic!()}
         
                     Self::check_mint_and_owner(reward_mint.key,winner_address.key,winner_ata)?;
                 }
@@ -579,65 +961,115 @@ impl Processor {
 
         participant.prize_claimed = 1;
 
-        participant.serialize(&mut &mut winner_pda.data.borrow_mut()[..])?;
+        Self::write_participant(&participant, winner_pda)?;
+        raffle.serialize(&mut &mut raffle_account.data.borrow_mut()[..])?;
 
         Ok(())
     }
 
     fn collect_fee_initializer(
-        accounts: &[AccountInfo],program_id: &Pubkey
+        accounts: &[AccountInfo],program_id: &Pubkey,data:FeeCurrency
     ) -> ProgramResult{
 
         let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
         let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
         let raffle_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-        let term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
         let fee_collector_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let fee_ledger_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
 
         let  mut raffle: Raffle = Raffle::try_from_slice(&raffle_pda.data.borrow())?;
-        let  terms: Term = Term::try_from_slice(&term_account.data.borrow())?;
         let  fee_collector: FeeCollector = FeeCollector::try_from_slice(&fee_collector_account.data.borrow())?;
 
-        if terms.initialized != 2 {return Err(InvalidTerms.into());}
         if fee_collector.initialized != 3 {return Err(InvalidFee.into());}
 
         if raffle_pda.owner != program_id {return Err(InvalidRaffle.into());}
-        if term_account.owner != program_id {return Err(InvalidRaffle.into());}
         if fee_collector_account.owner != program_id {return Err(InvalidRaffle.into());}
 
+        let (fee_collector_address, _bump) = Pubkey::find_program_address(&[b"fee_collector"], program_id);
+        if fee_collector_account.key != &fee_collector_address {return Err(InvalidFee.into());}
+
         if raffle.raffle_state < 2{return Err(InvalidRaffleState.into());}
-        if raffle.fee_collected != 0{return Err(InvalidRaffleState.into());}
         if raffle.initializer != initializer.key.to_bytes() {return Err(InvalidInitializer.into());}
 
-        if raffle.participation_fee_type == 1{
+        let (fee_type_no, fee_mint, fee_decimals): (u64, [u8;32], u8) = Self::fee_currency_of(&raffle, data.fee_currency)?;
+
+        //each currency is settled on its own, only participation_fee_mint carries the pool and the consolation escrow
+        let (fee_bps, collected_value, escrow_refund): (u64, u64, u64) = if data.fee_currency == 0 {
+
+            if raffle.raffle_state == 4 && raffle.consolation_escrow == 0 {return Err(InvalidRaffleState.into());}//fees are refunded to participants
+            if raffle.consolation_type != 0 && raffle.raffle_state == 2 {return Err(InvalidRaffleState.into());}//consolations are final once the winners are published
+            if raffle.fee_collected != 0{return Err(InvalidRaffleState.into());}
+
+            //taken from the counters, not the balance, so prizes already claimed or kept for a rollover do not skew it.
+            //consolations are paid from the escrow first and the fees cover the rest, unused escrow goes back to the initializer.
+            let is_refunding: bool = raffle.raffle_state == 4;
+            let consolation_owed: u64 = if is_refunding {0} else {raffle.consolation_reserved};
+            let from_escrow: u64 = consolation_owed.min(raffle.consolation_escrow);
+            let from_fees: u64 = consolation_owed.checked_sub(from_escrow).ok_or(ArithmeticError)?;
+            let escrow_refund: u64 = raffle.consolation_escrow.checked_sub(from_escrow).ok_or(ArithmeticError)?;
+
+            let collected_value: u64 = if is_refunding {0} else {
+                raffle.fees_received.checked_sub(raffle.fees_to_pool).ok_or(ArithmeticError)?
+                    .checked_sub(from_fees).ok_or(ArithmeticError)?
+            };
+
+            raffle.fee_collected = 1;
+
+            (raffle.fee_bps, collected_value, escrow_refund)
+
+        }else{
+
+            if raffle.raffle_state == 4 {return Err(InvalidRaffleState.into());}//fees are refunded to participants
+
+            let accepted: &mut AcceptedFee = &mut raffle.accepted_fees[data.fee_currency as usize - 1];
+
+            if accepted.fee_collected != 0 {return Err(InvalidRaffleState.into());}
+
+            accepted.fee_collected = 1;
+
+            (accepted.fee_bps, accepted.fees_received, 0)
+        };
+
+        if fee_type_no == 1{
 
 
-            let data_len: usize = raffle_pda.data_len();
             let rent: Rent = Rent::default();
-            let rent_amount: u64 = rent.minimum_balance(data_len);
-            let total_value: u64 = **raffle_pda.try_borrow_lamports()?;
-            let mut collected_value: u64 = total_value.checked_sub(rent_amount).ok_or(ArithmeticError)?;
-
-            if raffle.is_increasing_pool == 1 {
-                let total_rewards: u64 = raffle.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
-                collected_value = collected_value.checked_sub(total_rewards).ok_or(ArithmeticError)?;
-            }else if raffle.participation_fee_mint == raffle.reward_mint{
-                let total_rewards: u64 = raffle.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
-                collected_value = collected_value.checked_sub(total_rewards).ok_or(ArithmeticError)?;
-            }
 
-            let collected_value_div_by_100: u64 = collected_value.checked_div(100).ok_or(ArithmeticError)?;
-            let total_fee:u64 = collected_value_div_by_100.checked_mul(terms.fee_percent).ok_or(ArithmeticError)?;
-            let transfer_to_initializer: u64 = collected_value.checked_sub(total_fee).ok_or(ArithmeticError)?;
+            let (total_fee, transfer_to_initializer): (u64, u64) = split_fee(collected_value, fee_bps)?;
 
+            let collector_balance: u64 = fee_collector_account.lamports().checked_sub(rent.minimum_balance(1)).ok_or(ArithmeticError)?;
+            Self::accrue_fee(initializer, fee_ledger_account, fee_mint, collector_balance, total_fee, program_id)?;
 
             **raffle_pda.try_borrow_mut_lamports()? -= total_fee;
-            **raffle_pda.try_borrow_mut_lamports()? -= transfer_to_initializer;
-    
             **fee_collector_account.try_borrow_mut_lamports()? += total_fee;
-            **initializer.try_borrow_mut_lamports()? += transfer_to_initializer;
+
+            if raffle.payees.is_empty() {
+
+                **raffle_pda.try_borrow_mut_lamports()? -= transfer_to_initializer;
+                **initializer.try_borrow_mut_lamports()? += transfer_to_initializer;
+
+            }else{
+
+                let payee_amounts: Vec<u64> = split_among_payees(transfer_to_initializer, &raffle.payee_shares_bps)?;
+
+                for (i, amount) in payee_amounts.iter().enumerate() {
+
+                    let payee: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+                    if payee.key.to_bytes() != raffle.payees[i] {return Err(InvalidRevenueSplit.into());}
+
+                    **raffle_pda.try_borrow_mut_lamports()? -= amount;
+                    **payee.try_borrow_mut_lamports()? += amount;
+                }
+            }
+
+            if escrow_refund != 0 {
+
+                **raffle_pda.try_borrow_mut_lamports()? -= escrow_refund;
+                **initializer.try_borrow_mut_lamports()? += escrow_refund;
+            }
 
         }else{
 
@@ -647,28 +1079,40 @@ impl Processor {
             let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
             let participation_fee_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-            let raffle_ata_unpacked: spl_token::state::Account = Account::unpack_from_slice(&raffle_ata.data.borrow())?;
-            let mut collected_value: u64 = raffle_ata_unpacked.amount;
+            if participation_fee_mint.key.to_bytes() != fee_mint {return Err(InvalidMint.into());}
 
-            if raffle.is_increasing_pool == 1 {
-                let total_rewards: u64 = raffle.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
-                collected_value = collected_value.checked_sub(total_rewards).ok_or(ArithmeticError)?;
-            }else if raffle.participation_fee_mint == raffle.reward_mint{
-                let total_rewards: u64 = raffle.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
-                collected_value = collected_value.checked_sub(total_rewards).ok_or(ArithmeticError)?;
-            }
+            let (total_fee, transfer_to_initializer): (u64, u64) = split_fee(collected_value, fee_bps)?;
 
-            let collected_value_div_by_100: u64 = collected_value.checked_div(100).ok_or(ArithmeticError)?;
-            let total_fee:u64 = collected_value_div_by_100.checked_mul(terms.fee_percent).ok_or(ArithmeticError)?;
-            let transfer_to_initializer: u64 = collected_value.checked_sub(total_fee).ok_or(ArithmeticError)?;
+            Self::check_mint_and_owner(participation_fee_mint.key, fee_collector_account.key, fee_collector_ata)?;
+            let collector_balance: u64 = Account::unpack_from_slice(&fee_collector_ata.data.borrow())?.amount;
+            Self::accrue_fee(initializer, fee_ledger_account, fee_mint, collector_balance, total_fee, program_id)?;
 
+            if raffle.payees.is_empty() {
 
-            Self::transfer_tokens_from_raffle_pda(participation_fee_mint, raffle_ata, raffle_pda, initializer_ata, token_program, raffle.participation_fee_decimals, transfer_to_initializer, raffle.raffle_no, raffle.bump)?;
+                Self::transfer_tokens_from_raffle_pda(participation_fee_mint, raffle_ata, raffle_pda, initializer_ata, token_program, fee_decimals, transfer_to_initializer, raffle.raffle_no, raffle.bump)?;
 
-            Self::transfer_tokens_from_raffle_pda(participation_fee_mint, raffle_ata, raffle_pda, fee_collector_ata, token_program, raffle.participation_fee_decimals, total_fee, raffle.raffle_no, raffle.bump)?;
-        }
+            }else{
+
+                let payee_amounts: Vec<u64> = split_among_payees(transfer_to_initializer, &raffle.payee_shares_bps)?;
+
+                for (i, amount) in payee_amounts.iter().enumerate() {
+
+                    let payee_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+                    //same error as a wrong payee wallet in the SOL branch
+                    Self::check_mint_and_owner(participation_fee_mint.key, &Pubkey::new_from_array(raffle.payees[i]), payee_ata).map_err(|_| InvalidRevenueSplit)?;
+
+                    Self::transfer_tokens_from_raffle_pda(participation_fee_mint, raffle_ata, raffle_pda, payee_ata, token_program, fee_decimals, *amount, raffle.raffle_no, raffle.bump)?;
+                }
+            }
 
-        raffle.fee_collected = 1;
+            Self::transfer_tokens_from_raffle_pda(participation_fee_mint, raffle_ata, raffle_pda, fee_collector_ata, token_program, fee_decimals, total_fee, raffle.raffle_no, raffle.bump)?;
+
+            if escrow_refund != 0 {
+
+                Self::transfer_tokens_from_raffle_pda(participation_fee_mint, raffle_ata, raffle_pda, initializer_ata, token_program, fee_decimals, escrow_refund, raffle.raffle_no, raffle.bump)?;
+            }
+        }
 
         raffle.serialize(&mut &mut raffle_pda.data.borrow_mut()[..])?;
 
@@ -718,19 +1162,30 @@ impl Processor {
     }
 
     fn close_participant_pda(
-        accounts: &[AccountInfo]
+        accounts: &[AccountInfo], program_id: &Pubkey
     ) -> ProgramResult {
 
 
         let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
         let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-        let config: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
 
-        let config = Config::try_from_slice(&config.data.borrow())?;
+        let roles: Roles = Self::get_roles(roles_account, program_id)?;
     
-        Self::check_authority(authority.key, config)?;
+        Self::check_role(authority.key, &roles.treasurers)?;
+
+        if !authority.is_signer {
+            return Err(NotSignerAuth.into());
+        }
+
+        if raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
+
+        let raffle: Raffle = Raffle::try_from_slice(&raffle_account.data.borrow())?;
+
+        if raffle.raffle_state != 3 && raffle.raffle_state != 4 {return Err(InvalidRaffleState.into());}
 
         let total_loop: u64 = accounts_iter.len() as u64;
 
@@ -738,6 +1193,16 @@ impl Processor {
 
             let participant_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
+            if participant_pda.owner != program_id {return Err(InvalidParticipantPDA.into());}
+
+            let participant: Participant = Self::get_participant(participant_pda)?;
+
+            if participant.raffle_no != raffle.raffle_no {return Err(InvalidRaffleNo.into());}
+
+            //only entries with nothing left to pay out, a refund, prize, consolation or held referral share needs claim_prize first
+            let is_owed: bool = raffle.raffle_state == 4 || participant.entitled == 1 || raffle.consolation_type != 0 || participant.referral_share != 0;
+            if is_owed && participant.prize_claimed != 1 {return Err(InvalidParticipantPDA.into());}
+
             let value = **participant_pda.try_borrow_lamports()?;
 
             **participant_pda.try_borrow_mut_lamports()? -= value;
@@ -760,26 +1225,30 @@ impl Processor {
     let authority_3: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let authority_4: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let treasury: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
     let (config_address, bump) = Pubkey::find_program_address( &[b"config"], program_id);
 
     let rent: Rent = Rent::default();
-    let rent_amount: u64 = rent.minimum_balance(128);
+    let rent_amount: u64 = rent.minimum_balance(162);
 
-    if config_account.owner != program_id {
-        invoke_signed(
-            &system_instruction::create_account(
-                authority_1.key,
-                &config_address,
-                rent_amount,
-                128,
-                program_id,
-            ),
-            &[authority_1.clone(), config_account.clone()],
-            &[&[b"config", &[bump]]],
-        )?;
+    //an existing config can only be changed through set_config or a governance proposal
+    if config_account.owner == program_id {
+        return Err(InvalidConfig.into());
     }
 
+    invoke_signed(
+        &system_instruction::create_account(
+            authority_1.key,
+            &config_address,
+            rent_amount,
+            162,
+            program_id,
+        ),
+        &[authority_1.clone(), config_account.clone()],
+        &[&[b"config", &[bump]]],
+    )?;
+
 
     if !authority_1.is_signer {
         return Err(NotSignerAuth.into());
@@ -790,6 +1259,9 @@ impl Processor {
         authority_2: authority_2.key.to_bytes(),
         authority_3: authority_3.key.to_bytes(),
         authority_4: authority_4.key.to_bytes(),
+        governance_enabled: 0,
+        paused: 0,
+        treasury: treasury.key.to_bytes(),
     };
 
     config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
@@ -797,28 +1269,179 @@ impl Processor {
     Ok(())
 }
 
-    fn set_config(
+    //configs created before governance, pause and treasury were added only hold the four authorities
+    fn migrate_config(
         accounts: &[AccountInfo], program_id: &Pubkey
     ) -> ProgramResult {
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let authority_1: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let authority_2: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let authority_3: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let authority_4: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    
+    let treasury: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    if config_account.owner != program_id {
-        return Err(InvalidConfig.into());
-    }
+    let (config_address, _bump) = Pubkey::find_program_address( &[b"config"], program_id);
+
+    if config_account.owner != program_id {return Err(InvalidConfig.into());}
+    if config_account.key != &config_address {return Err(InvalidConfig.into());}
+    if config_account.data_len() != 128 {return Err(InvalidConfig.into());}
+
+    if !authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    let config_data: Config = {
+        let data = config_account.data.borrow();
+        let mut legacy: &[u8] = &data[..];
+        Config {
+            authority_1: <[u8;32]>::deserialize(&mut legacy)?,
+            authority_2: <[u8;32]>::deserialize(&mut legacy)?,
+            authority_3: <[u8;32]>::deserialize(&mut legacy)?,
+            authority_4: <[u8;32]>::deserialize(&mut legacy)?,
+            governance_enabled: 0,
+            paused: 0,
+            treasury: treasury.key.to_bytes(),
+        }
+    };
+
+    Self::check_authority(authority.key, config_data.clone())?;
+
+    Self::resize_account(config_account, authority, 162)?;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn migrate_raffle(
+        accounts: &[AccountInfo], program_id: &Pubkey
+    ) -> ProgramResult {
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let payer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if !payer.is_signer {return Err(InitializerNotSigner.into());}
+    if raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
+
+    //already in the current layout
+    if Raffle::try_from_slice(&raffle_account.data.borrow()).is_ok() {return Err(InvalidRaffle.into());}
+
+    let legacy: LegacyRaffle = LegacyRaffle::deserialize(&mut &raffle_account.data.borrow()[..])?;
+
+    let (raffle_address, _bump) = Pubkey::find_program_address(&[b"raffle", &legacy.raffle_no.to_le_bytes()], program_id);
+    if raffle_account.key != &raffle_address {return Err(InvalidRaffle.into());}
+
+    let terms: Term = Self::get_term(term_account, program_id)?;
+
+    //legacy raffles charged a flat fee and added transfer_fee_to_pool per entry
+    let fees_received: u64 = legacy.current_number_of_participants.checked_mul(legacy.participation_fee).ok_or(ArithmeticError)?;
+    let fees_to_pool: u64 = if legacy.is_increasing_pool == 1 {
+        let pool_per_entry: u64 = legacy.transfer_fee_to_pool.iter().try_fold(0u64, |sum, amount| sum.checked_add(*amount)).ok_or(ArithmeticError)?;
+        legacy.current_number_of_participants.checked_mul(pool_per_entry).ok_or(ArithmeticError)?
+    } else {0};
+
+    let raffle: Raffle = Raffle {
+        raffle_state: legacy.raffle_state,
+        is_unlimited_participant_allowed: legacy.is_unlimited_participant_allowed,
+        multiple_participation_allowed: legacy.multiple_participation_allowed,
+        initializer: legacy.initializer,
+        reward_mint: legacy.reward_mint,
+        raffle_name: legacy.raffle_name,
+        raffle_no: legacy.raffle_no,
+        current_number_of_participants: legacy.current_number_of_participants,
+        max_participants: legacy.participants_required,
+        participation_fee: legacy.participation_fee,
+        participation_fee_mint: legacy.participation_fee_mint,
+        participation_fee_type: legacy.participation_fee_type,
+        rewards: legacy.rewards,
+        winners: legacy.winners,
+        requirement_to_participate: legacy.requirement_to_participate,
+        requirement_amount_token: legacy.requirement_amount_token,
+        requirement_mint: legacy.requirement_mint,
+        required_token_decimals: legacy.required_token_decimals,
+        reward_decimals: legacy.reward_decimals,
+        participation_fee_decimals: legacy.participation_fee_decimals,
+        is_increasing_pool: legacy.is_increasing_pool,
+        transfer_fee_to_pool: legacy.transfer_fee_to_pool,
+        raffle_time: legacy.raffle_time,
+        winner_count: legacy.winner_count,
+        current_winner_count: legacy.current_winner_count,
+        number_of_entitled_winners: legacy.number_of_entitled_winners,
+        fee_collected: legacy.fee_collected,
+        fee_bps: terms.fee_bps,
+        expiration_time: terms.expiration_time,
+        maximum_winner_count: terms.maximum_winner_count,
+        created_at: 0,
+        referral_bps: 0,
+        payees: vec![],
+        payee_shares_bps: vec![],
+        allowlist_root: [0;32],
+        one_entry_per_nft: 0,
+        recheck_requirement_at_claim: 0,
+        max_entries_per_wallet: 0,
+        min_participants: 0,
+        refund_below_min: 0,
+        start_time: 0,
+        extension_count: 0,
+        series_id: 0,
+        round_no: 0,
+        rollover: 0,
+        claim_period: 0,
+        published_at: 0,
+        rewards_claimed: 0,
+        rollover_amount: 0,
+        rolled_over: 0,
+        pool_bps: 0,
+        pool_weights_bps: vec![],
+        fees_received,
+        fees_to_pool,
+        prize_shares_bps: vec![],
+        consolation_type: 0,
+        consolation_amount: 0,
+        consolation_escrow: 0,
+        consolation_reserved: 0,
+        pricing_mode: 0,
+        price_step_times: vec![],
+        price_step_prices: vec![],
+        end_price: 0,
+        attester: [0;32],
+        accepted_fees: vec![],
+        bump: legacy.bump,
+    };
+
+    let mut serialized_data: Vec<u8> = Vec::new();
+    raffle.serialize(&mut serialized_data)?;
+
+    Self::resize_account(raffle_account, payer, serialized_data.len())?;
+
+    raffle.serialize(&mut &mut raffle_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn set_config(
+        accounts: &[AccountInfo], program_id: &Pubkey
+    ) -> ProgramResult {
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let treasury: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     
 
-    let config: Config = Config::try_from_slice(&config_account.data.borrow())?;
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+    
 
+    let config: Config = Self::get_config(config_account, program_id)?;
 
-    Self::check_authority(authority.key, config)?;
+    if config.governance_enabled == 1 {
+        return Err(GovernanceEnabled.into());
+    }
+
+    Self::check_authority(authority.key, config.clone())?;
     
 
     if !authority.is_signer {
@@ -826,11 +1449,10 @@ impl Processor {
     }
     
 
+    //authority keys only change through propose_authority and accept_authority
     let config_data: Config = Config {
-        authority_1: authority_1.key.to_bytes(),
-        authority_2: authority_2.key.to_bytes(),
-        authority_3: authority_3.key.to_bytes(),
-        authority_4: authority_4.key.to_bytes(),
+        treasury: treasury.key.to_bytes(),
+        ..config
     };
     
 
@@ -848,16 +1470,11 @@ impl Processor {
 
     let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-
-    if config_account.owner != program_id {
-        return Err(InvalidConfig.into());
-    }
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    let config: Config = Config::try_from_slice(&config_account.data.borrow())?;
+    let roles: Roles = Self::get_roles(roles_account, program_id)?;
 
-
-    Self::check_authority(authority.key, config)?;
+    Self::check_role(authority.key, &roles.terms_admins)?;
 
     if !authority.is_signer {
         return Err(NotSignerAuth.into());
@@ -885,8 +1502,8 @@ impl Processor {
     )?;
 
     let terms: Term = Term { 
-        initialized: 2,
-        fee_percent: 0,
+        initialized: 14,
+        fee_bps: 0,
         expiration_time: 0,
         maximum_winner_count: 10,
     };
@@ -911,7 +1528,7 @@ impl Processor {
         return Err(InvalidConfig.into());
     }
 
-    let config: Config = Config::try_from_slice(&config_account.data.borrow())?;
+    let config: Config = Self::get_config(config_account, program_id)?;
 
 
     Self::check_authority(authority.key, config)?;
@@ -964,32 +1581,29 @@ impl Processor {
     let token_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let sysvar: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-
-    if config_account.owner != program_id {
-        return Err(InvalidConfig.into());
-    }
-
-    let config: Config = Config::try_from_slice(&config_account.data.borrow())?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
+    let roles: Roles = Self::get_roles(roles_account, program_id)?;
 
-    Self::check_authority(authority.key, config)?;
+    Self::check_role(authority.key, &roles.type_registrars)?;
 
     if !authority.is_signer {
         return Err(NotSignerAuth.into());
     }
 
+    Self::check_fee_schedule(fee_type.has_fee_bps, fee_type.fee_bps, fee_type.min_participation_fee, fee_type.max_participation_fee)?;
+
     let (fee_type_pda_address, bump) = Pubkey::find_program_address(&[b"feetype", &fee_type.no.to_le_bytes(), ], program_id);
 
     let rent: Rent = Rent::default();
-    let rent_amount: u64 = rent.minimum_balance(42);
+    let rent_amount: u64 = rent.minimum_balance(68);
 
     let create_ix: solana_program::instruction::Instruction =
         system_instruction::create_account(
             authority.key,
             &fee_type_pda_address,
             rent_amount,
-            42,
+            68,
             program_id,
         );
 
@@ -1026,39 +1640,47 @@ impl Processor {
     fn init_reward_type_account(
     accounts: &[AccountInfo],
     program_id: &Pubkey,
-    reward_type: RewardFeeType
+    data: RewardType
     ) -> ProgramResult {
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let reward_type_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-
-    if config_account.owner != program_id {
-        return Err(InvalidConfig.into());
-    }
-
-    let config: Config = Config::try_from_slice(&config_account.data.borrow())?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
+    let roles: Roles = Self::get_roles(roles_account, program_id)?;
 
-    Self::check_authority(authority.key, config)?;
+    Self::check_role(authority.key, &roles.type_registrars)?;
 
     if !authority.is_signer {
         return Err(NotSignerAuth.into());
     }
 
+    //same defaults as the reward types created before fee schedules
+    let reward_type: RewardFeeType = RewardFeeType{
+        initialized: data.initialized,
+        mint: data.mint,
+        decimals: data.decimals,
+        no: data.no,
+        enabled: 1,
+        has_fee_bps: 0,
+        fee_bps: 0,
+        min_participation_fee: 0,
+        max_participation_fee: 0,
+    };
+
     let (fee_type_pda_address, bump) = Pubkey::find_program_address(&[b"rewtype", &reward_type.no.to_le_bytes(), ], program_id);
 
     let rent: Rent = Rent::default();
-    let rent_amount: u64 = rent.minimum_balance(42);
+    let rent_amount: u64 = rent.minimum_balance(68);
 
     let create_ix: solana_program::instruction::Instruction =
         system_instruction::create_account(
             authority.key,
             &fee_type_pda_address,
             rent_amount,
-            42,
+            68,
             program_id,
         );
 
@@ -1071,59 +1693,114 @@ impl Processor {
 
     reward_type.serialize(&mut &mut reward_type_account.data.borrow_mut()[..])?;
 
+    Ok(())
+}
+
+    fn set_fee_schedule(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    data: FeeSchedule
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let fee_type_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let roles: Roles = Self::get_roles(roles_account, program_id)?;
+
+    Self::check_role(authority.key, &roles.type_registrars)?;
+
+    if !authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    if fee_type_account.owner != program_id {return Err(InvalidFeeType.into());}
+
+    let mut fee_type: RewardFeeType = Self::get_reward_fee_type(fee_type_account)?;
+
+    if fee_type.initialized != 3 {return Err(InvalidFeeType.into());}
+
+    let (fee_type_pda_address, _bump) = Pubkey::find_program_address(&[b"feetype", &fee_type.no.to_le_bytes(), ], program_id);
+    if fee_type_account.key != &fee_type_pda_address {return Err(InvalidFeeType.into());}
+
+    Self::check_fee_schedule(data.has_fee_bps, data.fee_bps, data.min_participation_fee, data.max_participation_fee)?;
+
+    fee_type.enabled = data.enabled;
+    fee_type.has_fee_bps = data.has_fee_bps;
+    fee_type.fee_bps = data.fee_bps;
+    fee_type.min_participation_fee = data.min_participation_fee;
+    fee_type.max_participation_fee = data.max_participation_fee;
+
+    //fee types created before schedules existed are 42 bytes long
+    if fee_type_account.data_len() != 68 {
+        Self::resize_account(fee_type_account, authority, 68)?;
+    }
+
+    fee_type.serialize(&mut &mut fee_type_account.data.borrow_mut()[..])?;
+
     Ok(())
 }
 
     fn collect_fee_token(
     accounts: &[AccountInfo],
     program_id: &Pubkey,
+    data: FeeWithdrawal,
     ) -> ProgramResult {
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let authority_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let treasury_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let fee_collector: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let fee_collector_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let participation_fee_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let fee_ledger_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    if config_account.owner != program_id {
-        return Err(InvalidConfig.into());
-    }
-
-
-    let config: Config = Config::try_from_slice(&config_account.data.borrow())?;
+    let roles: Roles = Self::get_roles(roles_account, program_id)?;
 
-    Self::check_authority(authority.key, config)?;
+    Self::check_role(authority.key, &roles.treasurers)?;
 
     if !authority.is_signer {
         return Err(NotSignerAuth.into());
     }
 
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+
+    let config: Config = Self::get_config(config_account, program_id)?;
 
-    let fee_collector_ata_unpacked: spl_token::state::Account = Account::unpack_from_slice(&fee_collector_ata.data.borrow())?;
-    let mint_unpacked: Mint = Mint::unpack(&participation_fee_mint.data.borrow())?;
+    Self::check_mint_and_owner(participation_fee_mint.key, &Pubkey::new_from_array(config.treasury), treasury_ata)?;
 
-    let collected_value: u64 = fee_collector_ata_unpacked.amount;
-    let decimals: u8 = mint_unpacked.decimals;
+    let mut fee_ledger: FeeLedger = Self::get_fee_ledger(fee_ledger_account, participation_fee_mint.key.to_bytes(), program_id)?;
 
+    Self::withdraw_fee(&mut fee_ledger, data.amount)?;
 
-    let transfer_collected_fee_to_initializer: Instruction = spl_token::instruction::transfer_checked(
-            &token_program.key,
-            &fee_collector_ata.key, 
-            &participation_fee_mint.key, 
-            &authority_ata.key, 
-            &fee_collector.key, 
-            &[],collected_value,decimals)?;
+    let decimals: u8 = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&participation_fee_mint.data.borrow())?.base.decimals;
+
+    let (_fee_collector_address, bump) = Pubkey::find_program_address(&[b"fee_collector"], program_id);
+
+    let transfer_collected_fee_to_treasury: Instruction = spl_token::instruction::transfer_checked(
+            token_program.key,
+            fee_collector_ata.key, 
+            participation_fee_mint.key, 
+            treasury_ata.key, 
+            fee_collector.key, 
+            &[],data.amount,decimals)?;
 
     invoke_signed(
-        &transfer_collected_fee_to_initializer, 
-        &[token_program.clone(),authority_ata.clone(),participation_fee_mint.clone(),fee_collector_ata.clone(),fee_collector.clone()],
-        &[&[b"fee_collector"]]
+        &transfer_collected_fee_to_treasury, 
+        &[token_program.clone(),treasury_ata.clone(),participation_fee_mint.clone(),fee_collector_ata.clone(),fee_collector.clone()],
+        &[&[b"fee_collector", &[bump]]]
         )?;
 
+    fee_ledger.serialize(&mut &mut fee_ledger_account.data.borrow_mut()[..])?;
+
     Ok(())
    }
 
@@ -1138,148 +1815,1991 @@ impl Processor {
     let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let pending_term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     
 
     if config_account.owner != program_id {
         return Err(InvalidConfig.into());
     }
+    if term_account.owner != program_id {
+        return Err(InvalidTerms.into());
+    }
+
 
+    let config: Config = Self::get_config(config_account, program_id)?;
 
-    let config: Config = Config::try_from_slice(&config_account.data.borrow())?;
+    if config.governance_enabled == 1 {
+        return Err(GovernanceEnabled.into());
+    }
 
+    let roles: Roles = Self::get_roles(roles_account, program_id)?;
 
-    Self::check_authority(authority.key, config)?;
+    Self::check_role(authority.key, &roles.terms_admins)?;
+
+    if !authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    //changes only take effect through apply_pending_term after the minimum delay
+    Self::queue_term(authority, pending_term_account, &data, program_id)?;
+
+    Ok(())
+}
+
+    fn set_term_delay(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    data: TermDelay,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let pending_term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+
+    let config: Config = Self::get_config(config_account, program_id)?;
+
+    if config.governance_enabled == 1 {
+        return Err(GovernanceEnabled.into());
+    }
+
+    let roles: Roles = Self::get_roles(roles_account, program_id)?;
+
+    Self::check_role(authority.key, &roles.terms_admins)?;
+
+    if !authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    Self::queue_term_delay(authority, pending_term_account, data.min_delay, program_id)?;
+
+    Ok(())
+}
+
+    fn apply_pending_term(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let pending_term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if term_account.owner != program_id {
+        return Err(InvalidTerms.into());
+    }
+    if pending_term_account.owner != program_id {
+        return Err(InvalidPendingTerm.into());
+    }
+
+    let (pending_term_address, _bump) = Pubkey::find_program_address(&[b"pending_term"], program_id);
+    if pending_term_account.key != &pending_term_address {return Err(InvalidPendingTerm.into());}
+
+    let mut terms: Term = Self::get_term(term_account, program_id)?;
+    let mut pending_term: PendingTerm = PendingTerm::try_from_slice(&pending_term_account.data.borrow())?;
+
+    if terms.initialized != 14 {return Err(InvalidTerms.into());}
+    if pending_term.initialized != 8 {return Err(InvalidPendingTerm.into());}
+
+    let clock: Clock= Clock::get()?;
+    let current_time: u64 = clock.unix_timestamp as u64;
+
+    let mut applied: bool = false;
+
+    //a delay raised after queueing also holds back the queued change
+    let term_effective_at: u64 = pending_term.term_effective_at.max(
+        pending_term.term_queued_at.checked_add(pending_term.min_delay).ok_or(ArithmeticError)?);
+
+    if pending_term.term_pending == 1 && current_time >= term_effective_at {
+
+        terms.fee_bps = pending_term.fee_bps;
+        terms.expiration_time = pending_term.expiration_time;
+        terms.maximum_winner_count = pending_term.maximum_winner_count;
+
+        pending_term.term_pending = 0;
+        applied = true;
+    }
+
+    if pending_term.delay_pending == 1 && current_time >= pending_term.delay_effective_at {
+
+        pending_term.min_delay = pending_term.pending_min_delay;
+
+        pending_term.delay_pending = 0;
+        applied = true;
+    }
+
+    if !applied {return Err(TermNotEffective.into());}
+
+    terms.serialize(&mut &mut term_account.data.borrow_mut()[..])?;
+    pending_term.serialize(&mut &mut pending_term_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn collect_fee(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    data: FeeWithdrawal,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let fee_collector: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let fee_ledger_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let treasury: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let roles: Roles = Self::get_roles(roles_account, program_id)?;
+
+    Self::check_role(authority.key, &roles.treasurers)?;
 
     if !authority.is_signer {
         return Err(NotSignerAuth.into());
     }
 
-    let terms: Term = Term{
-        initialized:2,
-        fee_percent: data.fee_percent,
-        expiration_time: data.expiration_time,
-        maximum_winner_count: data.maximum_winner_count
-    };
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+
+    let config: Config = Self::get_config(config_account, program_id)?;
+
+    if treasury.key.to_bytes() != config.treasury {return Err(InvalidTreasury.into());}
+
+    let (fee_collector_address, _bump) = Pubkey::find_program_address(&[b"fee_collector"], program_id);
+    if fee_collector.key != &fee_collector_address {return Err(InvalidFee.into());}
+
+    let mut fee_ledger: FeeLedger = Self::get_fee_ledger(fee_ledger_account, system_program::ID.to_bytes(), program_id)?;
+
+    Self::withdraw_fee(&mut fee_ledger, data.amount)?;
+
+    **fee_collector.try_borrow_mut_lamports()? -= data.amount;
+    **treasury.try_borrow_mut_lamports()? += data.amount;
+
+    fee_ledger.serialize(&mut &mut fee_ledger_account.data.borrow_mut()[..])?;
+    
+    Ok(())
+   }
+    
+   fn extend_raffle(
+        accounts: &[AccountInfo],program_id: &Pubkey,data:RaffleExtension
+   ) -> ProgramResult{
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
+    if !initializer.is_signer {return Err(InitializerNotSigner.into());}
+
+    let mut raffle: Raffle = Raffle::try_from_slice(&raffle_account.data.borrow())?;
+
+    if raffle.initializer != initializer.key.to_bytes() {return Err(InvalidInitializer.into());}
+
+    //no extension once the draw has started
+    if raffle.raffle_state != 1 {return Err(InvalidRaffleState.into());}
+    if raffle.current_winner_count != 0 {return Err(InvalidRaffleState.into());}
+
+    if raffle.extension_count >= MAX_EXTENSIONS {return Err(InvalidExtension.into());}
+
+    //a raffle past its time is waiting for the draw, it can not be reopened
+    let clock: Clock = Clock::get()?;
+    let current_time: u64 = clock.unix_timestamp as u64;
+    if current_time > raffle.raffle_time {return Err(InvalidRaffleTime.into());}
+
+    //migrated raffles have no created_at, their original raffle_time is the only known reference,
+    //it is kept so later extensions do not move the limit along with raffle_time
+    if raffle.created_at == 0 {raffle.created_at = raffle.raffle_time;}
+    let maximum_time_allowed: u64 = raffle.created_at.checked_add(raffle.expiration_time).ok_or(ArithmeticError)?;
+
+    if data.new_raffle_time <= raffle.raffle_time {return Err(InvalidExtension.into());}
+    if data.new_raffle_time > maximum_time_allowed {return Err(InvalidRaffleTime.into());}
+
+    raffle.raffle_time = data.new_raffle_time;
+    raffle.extension_count = raffle.extension_count.checked_add(1).ok_or(ArithmeticError)?;
+
+    raffle.serialize(&mut &mut raffle_account.data.borrow_mut()[..])?;
+
+    Ok(())
+   }
+
+   fn init_series(
+        accounts: &[AccountInfo],program_id: &Pubkey,data:InitSeries
+   ) -> ProgramResult{
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let creator: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let series_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let reward_type_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let fee_type_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    Self::check_not_paused(config_account, program_id)?;
+
+    if !creator.is_signer {return Err(InitializerNotSigner.into());}
+    if term_account.owner != program_id {return Err(InvalidTerms.into());}
+    if reward_type_pda.owner != program_id {return Err(InvalidRewardType.into());}
+    if fee_type_pda.owner != program_id {return Err(InvalidFeeType.into());}
+
+    let terms: Term = Self::get_term(term_account, program_id)?;
+    let reward_type: RewardFeeType = Self::get_reward_fee_type(reward_type_pda)?;
+    let fee_type: RewardFeeType = Self::get_reward_fee_type(fee_type_pda)?;
+
+    if terms.initialized != 14 {return Err(InvalidTerms.into());}
+    if reward_type.initialized != 2 {return Err(InvalidTerms.into());}
+    if fee_type.initialized != 3 {return Err(InvalidTerms.into());}
+    if reward_type.no != data.template.reward_type {return Err(InvalidRewardType.into());}
+    if fee_type.no != data.template.participation_fee_type {return Err(InvalidFeeType.into());}
+
+    let mut total_rewards:u64 = 0;
+    let mut participation_fee_total:u64 = 0;
+    Self::check_participation_reward_type_and_sum(program_id,&reward_type,&data.template.rewards,&data.template.transfer_fee_to_pool,reward_type_pda.key,&mut total_rewards,&mut participation_fee_total,data.template.participation_fee)?;
+    Self::check_price_schedule(program_id,&fee_type,fee_type_pda.key,&data.template)?;
+
+    //the template gets the same checks as init_raffle here, only the times are checked when each round is spawned
+    if data.series_id == 0 {return Err(InvalidSeries.into());}
+    if data.template.consolation_funding != 0 {return Err(InvalidConsolation.into());}//series rounds are only funded in the reward mint
+    if !data.template.accepted_fee_types.is_empty() {return Err(InvalidAcceptedFees.into());}//rounds are spawned without the accepted fee type accounts
+    if data.template.pricing_mode == 1 {return Err(InvalidPriceSchedule.into());}//step times are absolute, they would be stale for every round after the first
+    if data.duration == 0 {return Err(InvalidRaffleTime.into());}
+    if data.duration > terms.expiration_time {return Err(InvalidRaffleTime.into());}
+    Self::check_raffle_data(&data.template, &terms, reward_type.mint, fee_type.mint)?;
+
+    let (series_address, bump) = Pubkey::find_program_address(
+        &[b"series", creator.key.as_ref(), &data.series_id.to_le_bytes()],
+        program_id,
+    );
+
+    if series_account.key != &series_address {return Err(InvalidSeries.into());}
+
+    let series: RaffleSeries = RaffleSeries{
+        initialized: 13,
+        creator: creator.key.to_bytes(),
+        series_id: data.series_id,
+        round_no: 0,
+        current_raffle_no: 0,
+        active: 1,
+        duration: data.duration,
+        template: data.template,
+        bump,
+    };
+
+    let mut serialized_data: Vec<u8> = Vec::new();
+    series.serialize(&mut serialized_data)?;
+
+    let rent: Rent = Rent::default();
+    let rent_amount: u64 = rent.minimum_balance(serialized_data.len());
+
+    invoke_signed(
+        &system_instruction::create_account(
+            creator.key,
+            &series_address,
+            rent_amount,
+            serialized_data.len() as u64,
+            program_id,
+        ),
+        &[creator.clone(), series_account.clone()],
+        &[&[b"series", creator.key.as_ref(), &series.series_id.to_le_bytes(), &[bump]]],
+    )?;
+
+    series.serialize(&mut &mut series_account.data.borrow_mut()[..])?;
+
+    //token rounds are funded from the series ata, SOL rounds from the series account itself
+    if series.template.reward_type != 1 {
+
+        let reward_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let series_reward_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let reward_mint_token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let sysvar: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        if reward_mint.key.to_bytes() != reward_type.mint {return Err(InvalidMint.into());}
+
+        Self::create_ata(creator,series_account,series_reward_ata,reward_mint,reward_mint_token_program,sysvar)?;
+    }
+
+    Ok(())
+   }
+
+   fn spawn_series_round(
+        accounts: &[AccountInfo],program_id: &Pubkey
+   ) -> ProgramResult{
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let payer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let series_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let raffle_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let raffle_reward_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let raffle_fee_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let series_reward_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let counter_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let reward_type_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let fee_type_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let reward_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let reward_mint_token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let fee_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let fee_mint_token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let sysvar: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    Self::check_not_paused(config_account, program_id)?;
+
+    if series_account.owner != program_id {return Err(InvalidSeries.into());}
+
+    let mut series: RaffleSeries = RaffleSeries::try_from_slice(&series_account.data.borrow())?;
+
+    if series.initialized != 13 {return Err(InvalidSeries.into());}
+    if series.active != 1 {return Err(InvalidSeries.into());}
+
+    //a new round can only start once the previous one has been drawn
+    if series.round_no > 0 {
+
+        let previous_raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        let (previous_raffle_address, _bump) =
+        Pubkey::find_program_address(&[b"raffle", &series.current_raffle_no.to_le_bytes()], program_id);
+
+        if previous_raffle_account.key != &previous_raffle_address {return Err(InvalidRaffle.into());}
+
+        let previous_raffle: Raffle = Raffle::try_from_slice(&previous_raffle_account.data.borrow())?;
+
+        if previous_raffle.raffle_state < 3 {return Err(RoundNotSettled.into());}
+    }
+
+    let mut counter: RaffleCounter = RaffleCounter::try_from_slice(&counter_account.data.borrow())?;
+    let terms: Term = Self::get_term(term_account, program_id)?;
+    let reward_type: RewardFeeType = Self::get_reward_fee_type(reward_type_pda)?;
+    let fee_type: RewardFeeType = Self::get_reward_fee_type(fee_type_pda)?;
+
+    if reward_type.initialized != 2 {return Err(InvalidTerms.into());}
+    if fee_type.initialized != 3 {return Err(InvalidTerms.into());}
+    if reward_type.no != series.template.reward_type {return Err(InvalidRewardType.into());}
+    if fee_type.no != series.template.participation_fee_type {return Err(InvalidFeeType.into());}
+
+    counter.number_of_raffles = counter.number_of_raffles.checked_add(1).ok_or(ArithmeticError)?;
+
+    Self::check_accounts_init_raffle(&counter,&terms,payer,term_account,counter_account,reward_type_pda,fee_type_pda,program_id)?;
+
+    let mut total_rewards:u64 = 0;
+    let mut participation_fee_total:u64 = 0;
+    Self::check_participation_reward_type_and_sum(program_id,&reward_type,&series.template.rewards,&series.template.transfer_fee_to_pool,reward_type_pda.key,&mut total_rewards,&mut participation_fee_total,series.template.participation_fee)?;
+    Self::check_price_schedule(program_id,&fee_type,fee_type_pda.key,&series.template)?;
+
+    let fee_bps: u64 = if fee_type.has_fee_bps == 1 {fee_type.fee_bps} else {terms.fee_bps};
+
+    let clock: Clock = Clock::get()?;
+    let current_time: u64 = clock.unix_timestamp as u64;
+
+    let mut init_raffle: InitRaffle = series.template.clone();
+    init_raffle.raffle_time = current_time.checked_add(series.duration).ok_or(ArithmeticError)?;
+    init_raffle.start_time = 0;
+
+    let creator: Pubkey = Pubkey::new_from_array(series.creator);
+
+    let setup: RaffleSetup = RaffleSetup {
+        raffle_no: counter.number_of_raffles,
+        fee_bps,
+        reward_mint: reward_type.mint,
+        reward_decimals: reward_type.decimals,
+        participation_fee_mint: fee_type.mint,
+        participation_fee_decimals: fee_type.decimals,
+        accepted_fees: Vec::new(),
+    };
+
+    Self::check_and_write_raffle_data(&init_raffle,&terms,setup,raffle_pda, payer, &creator, program_id)?;
+
+    series.round_no = series.round_no.checked_add(1).ok_or(ArithmeticError)?;
+    series.current_raffle_no = counter.number_of_raffles;
+
+    let mut raffle: Raffle = Raffle::try_from_slice(&raffle_pda.data.borrow())?;
+    raffle.series_id = series.series_id;
+    raffle.round_no = series.round_no;
+    raffle.serialize(&mut &mut raffle_pda.data.borrow_mut()[..])?;
+
+    if init_raffle.reward_type != 1 {
+        Self::create_ata(payer,raffle_pda,raffle_reward_ata,reward_mint,reward_mint_token_program,sysvar)?;
+    }
+
+    if init_raffle.participation_fee_type != 1 && reward_type.mint != fee_type.mint {
+        Self::create_ata(payer,raffle_pda,raffle_fee_ata,fee_mint,fee_mint_token_program,sysvar)?;
+    }
+
+    if reward_type.no == 1 {
+
+        let rent: Rent = Rent::default();
+        let available: u64 = series_account.lamports().checked_sub(rent.minimum_balance(series_account.data_len())).ok_or(ArithmeticError)?;
+
+        if available < total_rewards {return Err(SeriesNotFunded.into());}
+
+        **series_account.try_borrow_mut_lamports()? -= total_rewards;
+        **raffle_pda.try_borrow_mut_lamports()? += total_rewards;
+
+    }else{
+
+        Self::check_mint_and_owner(reward_mint.key, series_account.key, series_reward_ata)?;
+
+        let series_ata_unpacked: Account = Account::unpack_from_slice(&series_reward_ata.data.borrow())?;
+
+        if series_ata_unpacked.amount < total_rewards {return Err(SeriesNotFunded.into());}
+
+        Self::transfer_tokens_from_series(reward_mint,series_reward_ata,series_account,raffle_reward_ata,reward_mint_token_program,total_rewards,&series)?;
+    }
+
+    if init_raffle.requirement_to_participate == 1 {
+
+        let raffle_reqired_token_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let required_token_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let required_mint_token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        if init_raffle.requirement_mint != required_token_mint.key.to_bytes() {return Err(InvalidMint.into())}
+
+        Self::create_ata(payer,raffle_pda,raffle_reqired_token_ata,required_token_mint,required_mint_token_program,sysvar)?;
+    }
+
+    series.serialize(&mut &mut series_account.data.borrow_mut()[..])?;
+    counter.serialize(&mut &mut counter_account.data.borrow_mut()[..])?;
+
+    Ok(())
+   }
+
+   fn close_series(
+        accounts: &[AccountInfo],program_id: &Pubkey
+   ) -> ProgramResult{
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let creator: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let series_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if series_account.owner != program_id {return Err(InvalidSeries.into());}
+    if !creator.is_signer {return Err(InitializerNotSigner.into());}
+
+    let mut series: RaffleSeries = RaffleSeries::try_from_slice(&series_account.data.borrow())?;
+
+    if series.initialized != 13 {return Err(InvalidSeries.into());}
+    if series.creator != creator.key.to_bytes() {return Err(InvalidInitializer.into());}
+    if series.active != 1 {return Err(InvalidSeries.into());}
+
+    //stops further rounds, a round already spawned runs to completion with its own escrow
+    series.active = 0;
+
+    if series.template.reward_type == 1 {
+
+        let rent: Rent = Rent::default();
+        let remaining: u64 = series_account.lamports().checked_sub(rent.minimum_balance(series_account.data_len())).ok_or(ArithmeticError)?;
+
+        **series_account.try_borrow_mut_lamports()? -= remaining;
+        **creator.try_borrow_mut_lamports()? += remaining;
+
+    }else{
+
+        let series_reward_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let creator_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let reward_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        Self::check_mint_and_owner(reward_mint.key, series_account.key, series_reward_ata)?;
+
+        let remaining: u64 = Account::unpack_from_slice(&series_reward_ata.data.borrow())?.amount;
+
+        if remaining > 0 {
+            Self::transfer_tokens_from_series(reward_mint,series_reward_ata,series_account,creator_ata,token_program,remaining,&series)?;
+        }
+    }
+
+    series.serialize(&mut &mut series_account.data.borrow_mut()[..])?;
+
+    Ok(())
+   }
+
+   fn rollover_pool(
+        accounts: &[AccountInfo],program_id: &Pubkey
+   ) -> ProgramResult{
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let caller: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let source_raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let target_raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if source_raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
+    if target_raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
+    if source_raffle_account.key == target_raffle_account.key {return Err(InvalidRollover.into());}
+
+    let mut source: Raffle = Raffle::try_from_slice(&source_raffle_account.data.borrow())?;
+    let mut target: Raffle = Raffle::try_from_slice(&target_raffle_account.data.borrow())?;
+
+    if source.rollover != 1 {return Err(InvalidRollover.into());}
+    if source.raffle_state != 3 && source.raffle_state != 4 {return Err(InvalidRaffleState.into());}
+
+    //a series rolls into any later round of the same series, anyone can crank it.
+    //outside a series the creator picks which of their raffles gets the pool.
+    if source.series_id != 0 {
+        if target.series_id != source.series_id {return Err(InvalidRollover.into());}
+        if target.round_no <= source.round_no {return Err(InvalidRollover.into());}
+    }else if !caller.is_signer || caller.key.to_bytes() != source.initializer {
+        return Err(InvalidInitializer.into());
+    }
+
+    if target.initializer != source.initializer {return Err(InvalidRollover.into());}
+    if target.reward_mint != source.reward_mint {return Err(InvalidMint.into());}
+    if target.raffle_state != 1 || target.current_winner_count != 0 {return Err(InvalidRaffleState.into());}
+    if target.rewards.is_empty() {return Err(InvalidRollover.into());}
+
+    let mut amount: u64 = source.rollover_amount;
+    source.rollover_amount = 0;
+
+    //prizes nobody claimed in time, including slots that never got a winner
+    if source.raffle_state == 3 && source.claim_period != 0 && source.rolled_over == 0 {
+
+        let clock: Clock = Clock::get()?;
+        let claim_deadline: u64 = source.published_at.checked_add(source.claim_period).ok_or(ArithmeticError)?;
+
+        if clock.unix_timestamp as u64 > claim_deadline {
+
+            let total_rewards: u64 = source.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+            let unclaimed: u64 = total_rewards.checked_sub(source.rewards_claimed).ok_or(ArithmeticError)?;
+
+            amount = amount.checked_add(unclaimed).ok_or(ArithmeticError)?;
+            source.rolled_over = 1;
+        }
+    }
+
+    if amount == 0 {return Err(InvalidRollover.into());}
+
+    if source.reward_mint == system_program::ID.to_bytes() {
+
+        **source_raffle_account.try_borrow_mut_lamports()? -= amount;
+        **target_raffle_account.try_borrow_mut_lamports()? += amount;
+
+    }else{
+
+        let source_reward_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let target_reward_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let reward_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        if reward_mint.key.to_bytes() != source.reward_mint {return Err(InvalidMint.into());}
+
+        Self::check_mint_and_owner(reward_mint.key, target_raffle_account.key, target_reward_ata)?;
+
+        Self::transfer_tokens_from_raffle_pda(reward_mint, source_reward_ata, source_raffle_account, target_reward_ata,
+            token_program, source.reward_decimals, amount, source.raffle_no, source.bump)?;
+    }
+
+    //the jackpot goes to the top prize of the next raffle
+    target.rewards[0] = target.rewards[0].checked_add(amount).ok_or(ArithmeticError)?;
+
+    source.serialize(&mut &mut source_raffle_account.data.borrow_mut()[..])?;
+    target.serialize(&mut &mut target_raffle_account.data.borrow_mut()[..])?;
+
+    Ok(())
+   }
+
+   fn add_solana_to_the_reward_pool(
+        accounts: &[AccountInfo],program_id: &Pubkey,amount:Rewards
+   ) -> ProgramResult{
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let payer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
+
+    let mut raffle: Raffle = Raffle::try_from_slice(&raffle_account.data.borrow())?;
+
+    if raffle.raffle_state != 1 {return Err(InvalidRaffleState.into());}
+
+    let total_rewards = amount.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+
+    invoke(&system_instruction::transfer(
+        payer.key,
+        raffle_account.key, 
+        total_rewards), 
+        &[payer.clone(),raffle_account.clone()])?;
+
+    if raffle.rewards.len() != amount.rewards.len() {return Err(ArithmeticError.into());}
+
+
+    for i in 0..amount.rewards.len() {
+
+        raffle.rewards[i] = raffle.rewards[i].checked_add(amount.rewards[i]).ok_or(ArithmeticError)?;
+
+    }
+
+
+    raffle.serialize(&mut &mut raffle_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+   fn add_tokens_to_the_reward_pool(
+    accounts: &[AccountInfo],program_id: &Pubkey,amount:Rewards
+   ) -> ProgramResult{
+
+let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+let payer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+let payer_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+let raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+let raffle_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+let reward_token_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+if raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
+
+let mut raffle: Raffle = Raffle::try_from_slice(&raffle_account.data.borrow())?;
+
+if raffle.raffle_state != 1 {return Err(InvalidRaffleState.into());}
+
+let total_rewards = amount.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+
+Self::transfer_tokens_to_raffle_pda(reward_token_mint, raffle_ata, payer, payer_ata, token_program, 
+    raffle.reward_decimals, total_rewards)?;
+
+if raffle.rewards.len() != amount.rewards.len() {return Err(ArithmeticError.into());}
+
+
+for i in 0..amount.rewards.len() {
+
+    raffle.rewards[i] = raffle.rewards[i].checked_add(amount.rewards[i]).ok_or(ArithmeticError)?;
+
+}
+
+
+raffle.serialize(&mut &mut raffle_account.data.borrow_mut()[..])?;
+
+Ok(())
+}
+
+
+    fn withdraw_referral(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let referrer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let referral_pda: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if referral_pda.owner != program_id {return Err(InvalidReferral.into());}
+    if !referrer.is_signer {return Err(NotSignerAuth.into());}
+
+    let mut referral: Referral = Referral::try_from_slice(&referral_pda.data.borrow())?;
+
+    if referral.initialized != 9 {return Err(InvalidReferral.into());}
+    if referral.referrer != referrer.key.to_bytes() {return Err(InvalidReferral.into());}
+
+    let amount: u64;
+
+    if referral.mint == system_program::ID.to_bytes() {
+
+        let rent: Rent = Rent::default();
+        let rent_amount: u64 = rent.minimum_balance(referral_pda.data_len());
+
+        amount = referral_pda.lamports().checked_sub(rent_amount).ok_or(ArithmeticError)?;
+
+        **referral_pda.try_borrow_mut_lamports()? -= amount;
+        **referrer.try_borrow_mut_lamports()? += amount;
+
+    }else{
+
+        let referral_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let referrer_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        if referral.mint != mint.key.to_bytes() {return Err(InvalidMint.into());}
+
+        Self::check_mint_and_owner(mint.key, referral_pda.key, referral_ata)?;
+
+        let referral_ata_unpacked: Account = Account::unpack_from_slice(&referral_ata.data.borrow())?;
+        let decimals: u8 = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint.data.borrow())?.base.decimals;
+
+        amount = referral_ata_unpacked.amount;
+
+        let transfer_token_ix: Instruction = spl_token::instruction::transfer_checked(
+            token_program.key,
+            referral_ata.key,
+            mint.key,
+            referrer_ata.key,
+            referral_pda.key,
+            &[],amount,decimals)?;
+
+        invoke_signed(
+            &transfer_token_ix,
+            &[token_program.clone(),referral_ata.clone(),mint.clone(),referrer_ata.clone(),referral_pda.clone()],
+            &[&[b"referral", &referral.referrer, &referral.mint, &[referral.bump]]],
+        )?;
+    }
+
+    referral.withdrawn = referral.withdrawn.checked_add(amount).ok_or(ArithmeticError)?;
+
+    referral.serialize(&mut &mut referral_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn init_governance(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    data: GovernanceSettings,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let governance_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+
+    let mut config: Config = Self::get_config(config_account, program_id)?;
+
+    if config.governance_enabled == 1 {
+        return Err(GovernanceEnabled.into());
+    }
+
+    Self::check_authority(authority.key, config.clone())?;
+
+    if !authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    Self::check_governance_settings(&data)?;
+
+    //governance can not be turned off again, a majority of the config authorities has to sign for the first members
+    let authority_slots: [[u8;32]; 4] = [config.authority_1, config.authority_2, config.authority_3, config.authority_4];
+    let signers: Vec<[u8;32]> = std::iter::once(authority).chain(accounts_iter.by_ref())
+        .filter(|account| account.is_signer).map(|account| account.key.to_bytes()).collect();
+    let approvals: usize = authority_slots.iter().filter(|slot| signers.contains(slot)).count();
+    if approvals < 3 {return Err(ThresholdNotReached.into());}
+
+    let (governance_address, bump) = Pubkey::find_program_address(&[b"governance"], program_id);
+
+    if governance_account.key != &governance_address {
+        return Err(InvalidGovernance.into());
+    }
+
+    let governance: Governance = Governance {
+        initialized: 4,
+        threshold: data.threshold,
+        version: 1,
+        proposal_count: 0,
+        members: data.members,
+    };
+
+    let mut serialized_data: Vec<u8> = Vec::new();
+    governance.serialize(&mut serialized_data)?;
+
+    let rent: Rent = Rent::default();
+    let rent_amount: u64 = rent.minimum_balance(serialized_data.len());
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &governance_address,
+            rent_amount,
+            serialized_data.len() as u64,
+            program_id,
+        ),
+        &[authority.clone(), governance_account.clone()],
+        &[&[b"governance", &[bump]]],
+    )?;
+
+    governance.serialize(&mut &mut governance_account.data.borrow_mut()[..])?;
+
+    config.governance_enabled = 1;
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn create_proposal(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    data: ProposalData,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let proposer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let governance_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let proposal_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if governance_account.owner != program_id {
+        return Err(InvalidGovernance.into());
+    }
+
+    if !proposer.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    let mut governance: Governance = Governance::try_from_slice(&governance_account.data.borrow())?;
+
+    if governance.initialized != 4 {
+        return Err(InvalidGovernance.into());
+    }
+
+    let member_index: usize = Self::check_governance_member(proposer.key, &governance)?;
+
+    //payload is validated up front so that a bad proposal cannot collect approvals
+    Self::check_proposal_payload(&data)?;
+
+    governance.proposal_count = governance.proposal_count.checked_add(1).ok_or(ArithmeticError)?;
+
+    let (proposal_address, bump) = Pubkey::find_program_address(
+        &[b"proposal", &governance.proposal_count.to_le_bytes()],
+        program_id,
+    );
+
+    if proposal_account.key != &proposal_address {
+        return Err(InvalidProposal.into());
+    }
+
+    let proposal: Proposal = Proposal {
+        initialized: 5,
+        proposal_no: governance.proposal_count,
+        governance_version: governance.version,
+        proposer: proposer.key.to_bytes(),
+        action: data.action,
+        approvals: 1 << member_index,
+        executed: 0,
+        payload: data.payload,
+    };
+
+    let mut serialized_data: Vec<u8> = Vec::new();
+    proposal.serialize(&mut serialized_data)?;
+
+    let rent: Rent = Rent::default();
+    let rent_amount: u64 = rent.minimum_balance(serialized_data.len());
+
+    invoke_signed(
+        &system_instruction::create_account(
+            proposer.key,
+            &proposal_address,
+            rent_amount,
+            serialized_data.len() as u64,
+            program_id,
+        ),
+        &[proposer.clone(), proposal_account.clone()],
+        &[&[b"proposal", &governance.proposal_count.to_le_bytes(), &[bump]]],
+    )?;
+
+    proposal.serialize(&mut &mut proposal_account.data.borrow_mut()[..])?;
+    governance.serialize(&mut &mut governance_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn approve_proposal(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let member: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let governance_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let proposal_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if governance_account.owner != program_id {
+        return Err(InvalidGovernance.into());
+    }
+    if proposal_account.owner != program_id {
+        return Err(InvalidProposal.into());
+    }
+
+    if !member.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    let governance: Governance = Governance::try_from_slice(&governance_account.data.borrow())?;
+    let mut proposal: Proposal = Proposal::try_from_slice(&proposal_account.data.borrow())?;
+
+    Self::check_open_proposal(&governance, &proposal)?;
+
+    let member_index: usize = Self::check_governance_member(member.key, &governance)?;
+
+    if proposal.approvals & (1 << member_index) != 0 {
+        return Err(InvalidProposal.into());
+    }
+
+    proposal.approvals |= 1 << member_index;
+
+    proposal.serialize(&mut &mut proposal_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn execute_proposal(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let executor: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let governance_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let proposal_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if governance_account.owner != program_id {
+        return Err(InvalidGovernance.into());
+    }
+    if proposal_account.owner != program_id {
+        return Err(InvalidProposal.into());
+    }
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+
+    if !executor.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    let mut governance: Governance = Governance::try_from_slice(&governance_account.data.borrow())?;
+    let mut proposal: Proposal = Proposal::try_from_slice(&proposal_account.data.borrow())?;
+    let mut config: Config = Self::get_config(config_account, program_id)?;
+
+    if config.governance_enabled != 1 {
+        return Err(InvalidConfig.into());
+    }
+
+    Self::check_open_proposal(&governance, &proposal)?;
+    Self::check_governance_member(executor.key, &governance)?;
+
+    if proposal.approvals.count_ones() < governance.threshold as u32 {
+        return Err(ThresholdNotReached.into());
+    }
+
+    //authority keys only change through action 5, the new key has to accept its slot
+    if proposal.action == 2 {
+
+        let pending_term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        let data: Term = Term::try_from_slice(&proposal.payload)?;
+
+        Self::queue_term(executor, pending_term_account, &data, program_id)?;
+
+    }else if proposal.action == 3 {
+
+        let settings: GovernanceSettings = GovernanceSettings::try_from_slice(&proposal.payload)?;
+
+        governance.threshold = settings.threshold;
+        governance.members = settings.members;
+        governance.version = governance.version.checked_add(1).ok_or(ArithmeticError)?;
+
+        let mut serialized_data: Vec<u8> = Vec::new();
+        governance.serialize(&mut serialized_data)?;
+
+        Self::resize_account(governance_account, executor, serialized_data.len())?;
+
+        governance.serialize(&mut &mut governance_account.data.borrow_mut()[..])?;
+
+    }else if proposal.action == 4 {
+
+        let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        Self::get_roles(roles_account, program_id)?;
+
+        let mut roles: Roles = Roles::try_from_slice(&proposal.payload)?;
+        roles.initialized = 6;
+
+        Self::write_roles(roles_account, executor, &roles)?;
+
+    }else if proposal.action == 5 {
+
+        let rotation_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        let data: AuthorityProposal = AuthorityProposal::try_from_slice(&proposal.payload)?;
+
+        Self::write_authority_rotation(executor, rotation_account, &config, data, program_id)?;
+
+    }else if proposal.action == 6 {
+
+        let pending_term_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+        let data: TermDelay = TermDelay::try_from_slice(&proposal.payload)?;
+
+        Self::queue_term_delay(executor, pending_term_account, data.min_delay, program_id)?;
+
+    }else if proposal.action == 7 {
+
+        let data: ConfigTreasury = ConfigTreasury::try_from_slice(&proposal.payload)?;
+
+        config.treasury = data.treasury;
+
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    }else{
+        return Err(InvalidProposal.into());
+    }
+
+    proposal.executed = 1;
+
+    proposal.serialize(&mut &mut proposal_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn init_roles(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    data: Roles,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+
+    let config: Config = Self::get_config(config_account, program_id)?;
+
+    Self::check_authority(authority.key, config)?;
+
+    if !authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    let (roles_address, bump) = Pubkey::find_program_address(&[b"roles"], program_id);
+
+    if roles_account.key != &roles_address {
+        return Err(InvalidRoles.into());
+    }
+
+    let roles: Roles = Roles {
+        initialized: 6,
+        ..data
+    };
+
+    let mut serialized_data: Vec<u8> = Vec::new();
+    roles.serialize(&mut serialized_data)?;
+
+    let rent: Rent = Rent::default();
+    let rent_amount: u64 = rent.minimum_balance(serialized_data.len());
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            &roles_address,
+            rent_amount,
+            serialized_data.len() as u64,
+            program_id,
+        ),
+        &[authority.clone(), roles_account.clone()],
+        &[&[b"roles", &[bump]]],
+    )?;
+
+    roles.serialize(&mut &mut roles_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn set_roles(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    data: Roles,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+
+    let config: Config = Self::get_config(config_account, program_id)?;
+
+    if config.governance_enabled == 1 {
+        return Err(GovernanceEnabled.into());
+    }
+
+    Self::check_authority(authority.key, config)?;
+
+    if !authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    Self::get_roles(roles_account, program_id)?;
+
+    let roles: Roles = Roles {
+        initialized: 6,
+        ..data
+    };
+
+    Self::write_roles(roles_account, authority, &roles)?;
+
+    Ok(())
+}
+
+    fn set_pause(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    paused: u8,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let pauser: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let roles_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+
+    let roles: Roles = Self::get_roles(roles_account, program_id)?;
+
+    Self::check_role(pauser.key, &roles.pausers)?;
+
+    if !pauser.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    let mut config: Config = Self::get_config(config_account, program_id)?;
+
+    config.paused = paused;
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn get_reward_fee_type(
+        type_account: &AccountInfo
+    ) -> Result<RewardFeeType, ProgramError> {
+
+        let data = type_account.data.borrow();
+
+        //accounts created before fee schedules were added only hold the first four fields
+        if data.len() == 42 {
+            let mut legacy: &[u8] = &data[..];
+            return Ok(RewardFeeType{
+                initialized: u8::deserialize(&mut legacy)?,
+                mint: <[u8;32]>::deserialize(&mut legacy)?,
+                decimals: u8::deserialize(&mut legacy)?,
+                no: u64::deserialize(&mut legacy)?,
+                enabled: 1,
+                has_fee_bps: 0,
+                fee_bps: 0,
+                min_participation_fee: 0,
+                max_participation_fee: 0,
+            });
+        }
+
+        let reward_fee_type: RewardFeeType = RewardFeeType::try_from_slice(&data)?;
+
+        Ok(reward_fee_type)
+    }
+
+    fn check_fee_schedule(
+        has_fee_bps: u8, fee_bps: u64, min_participation_fee: u64, max_participation_fee: u64
+    ) -> ProgramResult {
+
+        if has_fee_bps == 1 && fee_bps > BPS_DENOMINATOR {return Err(InvalidTerms.into());}
+        if max_participation_fee != 0 && min_participation_fee > max_participation_fee {
+            return Err(InvalidParticipationFee.into());
+        }
+
+        Ok(())
+    }
+
+    fn get_roles(
+        roles_account: &AccountInfo, program_id: &Pubkey
+    ) -> Result<Roles, ProgramError> {
+
+        if roles_account.owner != program_id {return Err(InvalidRoles.into());}
+
+        let (roles_address, _bump) = Pubkey::find_program_address(&[b"roles"], program_id);
+        if roles_account.key != &roles_address {return Err(InvalidRoles.into());}
+
+        let roles: Roles = Roles::try_from_slice(&roles_account.data.borrow())?;
+
+        if roles.initialized != 6 {return Err(InvalidRoles.into());}
+
+        Ok(roles)
+    }
+
+    fn check_role(
+        authority: &Pubkey, role_keys: &[[u8;32]]
+    ) -> ProgramResult {
+
+        if !role_keys.contains(&authority.to_bytes()) {
+            return Err(InvalidAuth.into());
+        }
+
+        Ok(())
+    }
+
+    fn write_roles<'a>(
+        roles_account: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        roles: &Roles,
+    ) -> ProgramResult {
+
+        let mut serialized_data: Vec<u8> = Vec::new();
+        roles.serialize(&mut serialized_data)?;
+
+        Self::resize_account(roles_account, payer, serialized_data.len())?;
+
+        roles.serialize(&mut &mut roles_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    //only the config pda is trusted, any other program owned account could deserialize as a Config
+    fn get_config(
+        config_account: &AccountInfo, program_id: &Pubkey
+    ) -> Result<Config, ProgramError> {
+
+        if config_account.owner != program_id {return Err(InvalidConfig.into());}
+
+        let (config_address, _bump) = Pubkey::find_program_address(&[b"config"], program_id);
+        if config_account.key != &config_address {return Err(InvalidConfig.into());}
+
+        let config: Config = Config::try_from_slice(&config_account.data.borrow())?;
+
+        Ok(config)
+    }
+
+    fn get_term(
+        term_account: &AccountInfo, program_id: &Pubkey
+    ) -> Result<Term, ProgramError> {
+
+        if term_account.owner != program_id {return Err(InvalidTerms.into());}
+
+        let (term_address, _bump) = Pubkey::find_program_address(&[b"term"], program_id);
+        if term_account.key != &term_address {return Err(InvalidTerms.into());}
+
+        let mut terms: Term = Term::try_from_slice(&term_account.data.borrow())?;
+
+        //terms written before basis points kept the fee in percent
+        if terms.initialized == 2 {
+            terms.fee_bps = terms.fee_bps.checked_mul(100).ok_or(ArithmeticError)?;
+            terms.initialized = 14;
+        }
+
+        Ok(terms)
+    }
+
+    fn check_not_paused(
+        config_account: &AccountInfo, program_id: &Pubkey
+    ) -> ProgramResult {
+
+        let config: Config = Self::get_config(config_account, program_id)?;
+
+        if config.paused == 1 {return Err(ProgramPaused.into());}
+
+        Ok(())
+    }
+
+    fn propose_authority(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    data: AuthorityProposal,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let rotation_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+
+    let config: Config = Self::get_config(config_account, program_id)?;
+
+    if config.governance_enabled == 1 {
+        return Err(GovernanceEnabled.into());
+    }
+
+    Self::check_authority(authority.key, config.clone())?;
+
+    if !authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    Self::write_authority_rotation(authority, rotation_account, &config, data, program_id)?;
+
+    Ok(())
+}
+
+    fn accept_authority(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let new_authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let rotation_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+    if rotation_account.owner != program_id {
+        return Err(InvalidRotation.into());
+    }
+
+    if !new_authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    let mut config: Config = Self::get_config(config_account, program_id)?;
+    let mut rotation: AuthorityRotation = AuthorityRotation::try_from_slice(&rotation_account.data.borrow())?;
+
+    if rotation.initialized != 7 {return Err(InvalidRotation.into());}
+
+    let (rotation_address, _bump) = Pubkey::find_program_address(&[b"rotation", &[rotation.slot]], program_id);
+    if rotation_account.key != &rotation_address {return Err(InvalidRotation.into());}
+
+    if rotation.pending_authority != new_authority.key.to_bytes() {return Err(InvalidAuth.into());}
+
+    let slot: &mut [u8;32] = Self::get_authority_slot(&mut config, rotation.slot)?;
+
+    //slot was overwritten after the rotation was proposed
+    if *slot != rotation.current_authority {return Err(InvalidRotation.into());}
+
+    *slot = rotation.pending_authority;
+
+    rotation.current_authority = rotation.pending_authority;
+    rotation.pending_authority = [0; 32];
+
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    rotation.serialize(&mut &mut rotation_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn cancel_authority(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    ) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let rotation_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if config_account.owner != program_id {
+        return Err(InvalidConfig.into());
+    }
+    if rotation_account.owner != program_id {
+        return Err(InvalidRotation.into());
+    }
+
+    if !authority.is_signer {
+        return Err(NotSignerAuth.into());
+    }
+
+    let config: Config = Self::get_config(config_account, program_id)?;
+    let mut rotation: AuthorityRotation = AuthorityRotation::try_from_slice(&rotation_account.data.borrow())?;
+
+    if rotation.initialized != 7 {return Err(InvalidRotation.into());}
+
+    let (rotation_address, _bump) = Pubkey::find_program_address(&[b"rotation", &[rotation.slot]], program_id);
+    if rotation_account.key != &rotation_address {return Err(InvalidRotation.into());}
+
+    if rotation.pending_authority == [0; 32] {return Err(InvalidRotation.into());}
+
+    //the pending key can always decline, under governance nobody else can cancel
+    if rotation.pending_authority != authority.key.to_bytes() {
+
+        if config.governance_enabled == 1 {
+            return Err(GovernanceEnabled.into());
+        }
+
+        Self::check_authority(authority.key, config)?;
+    }
+
+    rotation.pending_authority = [0; 32];
+
+    rotation.serialize(&mut &mut rotation_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+    fn check_governance_settings(
+        settings: &GovernanceSettings
+    ) -> ProgramResult {
+
+        if settings.members.is_empty() || settings.members.len() > 64 {return Err(InvalidGovernance.into());}
+        if settings.threshold == 0 || settings.threshold as usize > settings.members.len() {return Err(InvalidGovernance.into());}
+        if settings.threshold == 1 && settings.members.len() > 1 {return Err(InvalidGovernance.into());}
+
+        for (i, member) in settings.members.iter().enumerate() {
+            if member == &[0; 32] {return Err(InvalidGovernance.into());}
+            if settings.members[..i].contains(member) {return Err(InvalidGovernance.into());}
+        }
+
+        Ok(())
+    }
+
+    fn check_governance_member(
+        member: &Pubkey, governance: &Governance
+    ) -> Result<usize, ProgramError> {
+
+        let index: usize = governance.members.iter().position(|x| x == &member.to_bytes()).ok_or(NotGovernanceMember)?;
+
+        Ok(index)
+    }
+
+    fn check_open_proposal(
+        governance: &Governance, proposal: &Proposal
+    ) -> ProgramResult {
+
+        if governance.initialized != 4 {return Err(InvalidGovernance.into());}
+        if proposal.initialized != 5 {return Err(InvalidProposal.into());}
+        if proposal.executed != 0 {return Err(InvalidProposal.into());}
+
+        //members changed after the proposal was created
+        if proposal.governance_version != governance.version {return Err(InvalidProposal.into());}
+
+        Ok(())
+    }
+
+    fn check_proposal_payload(
+        data: &ProposalData
+    ) -> ProgramResult {
+
+        if data.action == 2 {
+            let term: Term = Term::try_from_slice(&data.payload)?;
+            if term.initialized != 14 {return Err(InvalidTerms.into());}
+        }else if data.action == 3 {
+            let settings: GovernanceSettings = GovernanceSettings::try_from_slice(&data.payload)?;
+            Self::check_governance_settings(&settings)?;
+        }else if data.action == 4 {
+            Roles::try_from_slice(&data.payload)?;
+        }else if data.action == 5 {
+            AuthorityProposal::try_from_slice(&data.payload)?;
+        }else if data.action == 6 {
+            TermDelay::try_from_slice(&data.payload)?;
+        }else if data.action == 7 {
+            ConfigTreasury::try_from_slice(&data.payload)?;
+        }else{
+            return Err(InvalidProposal.into());
+        }
+
+        Ok(())
+    }
+
+    fn get_authority_slot(
+        config: &mut Config, slot: u8
+    ) -> Result<&mut [u8;32], ProgramError> {
+
+        match slot {
+            1 => Ok(&mut config.authority_1),
+            2 => Ok(&mut config.authority_2),
+            3 => Ok(&mut config.authority_3),
+            4 => Ok(&mut config.authority_4),
+            _ => Err(InvalidAuthoritySlot.into()),
+        }
+    }
+
+    fn write_authority_rotation<'a>(
+        payer: &AccountInfo<'a>,
+        rotation_account: &AccountInfo<'a>,
+        config: &Config,
+        data: AuthorityProposal,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        if data.new_authority == [0; 32] {return Err(InvalidAuth.into());}
+
+        let current_authority: [u8;32] = *Self::get_authority_slot(&mut config.clone(), data.slot)?;
+
+        let (rotation_address, bump) = Pubkey::find_program_address(&[b"rotation", &[data.slot]], program_id);
+
+        if rotation_account.key != &rotation_address {return Err(InvalidRotation.into());}
+
+        if rotation_account.owner != program_id {
+
+            let rent: Rent = Rent::default();
+            let rent_amount: u64 = rent.minimum_balance(66);
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    &rotation_address,
+                    rent_amount,
+                    66,
+                    program_id,
+                ),
+                &[payer.clone(), rotation_account.clone()],
+                &[&[b"rotation", &[data.slot], &[bump]]],
+            )?;
+        }
+
+        let rotation: AuthorityRotation = AuthorityRotation {
+            initialized: 7,
+            slot: data.slot,
+            current_authority,
+            pending_authority: data.new_authority,
+        };
+
+        rotation.serialize(&mut &mut rotation_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn get_or_create_pending_term<'a>(
+        payer: &AccountInfo<'a>,
+        pending_term_account: &AccountInfo<'a>,
+        program_id: &Pubkey,
+    ) -> Result<PendingTerm, ProgramError> {
+
+        let (pending_term_address, bump) = Pubkey::find_program_address(&[b"pending_term"], program_id);
+
+        if pending_term_account.key != &pending_term_address {return Err(InvalidPendingTerm.into());}
+
+        if pending_term_account.owner == program_id {
+            return Ok(PendingTerm::try_from_slice(&pending_term_account.data.borrow())?);
+        }
+
+        let rent: Rent = Rent::default();
+        let rent_amount: u64 = rent.minimum_balance(67);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                &pending_term_address,
+                rent_amount,
+                67,
+                program_id,
+            ),
+            &[payer.clone(), pending_term_account.clone()],
+            &[&[b"pending_term", &[bump]]],
+        )?;
+
+        Ok(PendingTerm {
+            initialized: 8,
+            min_delay: MIN_TERM_DELAY,
+            term_pending: 0,
+            term_effective_at: 0,
+            fee_bps: 0,
+            expiration_time: 0,
+            maximum_winner_count: 0,
+            delay_pending: 0,
+            delay_effective_at: 0,
+            pending_min_delay: 0,
+            term_queued_at: 0,
+        })
+    }
+
+    fn queue_term<'a>(
+        payer: &AccountInfo<'a>,
+        pending_term_account: &AccountInfo<'a>,
+        data: &Term,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        //14 marks fee_bps in basis points, a term still written in percent is refused
+        if data.initialized != 14 {return Err(InvalidTerms.into());}
+        if data.fee_bps > BPS_DENOMINATOR {return Err(InvalidTerms.into());}
+
+        let mut pending_term: PendingTerm = Self::get_or_create_pending_term(payer, pending_term_account, program_id)?;
+
+        let clock: Clock= Clock::get()?;
+        let current_time: u64 = clock.unix_timestamp as u64;
+
+        //queueing again replaces the pending change and restarts the delay
+        pending_term.term_pending = 1;
+        pending_term.term_effective_at = current_time.checked_add(pending_term.min_delay).ok_or(ArithmeticError)?;
+        pending_term.term_queued_at = current_time;
+        pending_term.fee_bps = data.fee_bps;
+        pending_term.expiration_time = data.expiration_time;
+        pending_term.maximum_winner_count = data.maximum_winner_count;
+
+        pending_term.serialize(&mut &mut pending_term_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn queue_term_delay<'a>(
+        payer: &AccountInfo<'a>,
+        pending_term_account: &AccountInfo<'a>,
+        min_delay: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        if min_delay < MIN_TERM_DELAY {return Err(InvalidPendingTerm.into());}
+
+        let mut pending_term: PendingTerm = Self::get_or_create_pending_term(payer, pending_term_account, program_id)?;
+
+        if min_delay >= pending_term.min_delay {
+
+            //a longer delay only makes changes slower, so it applies at once
+            pending_term.min_delay = min_delay;
+            pending_term.delay_pending = 0;
+
+        }else{
+
+            let clock: Clock= Clock::get()?;
+            let current_time: u64 = clock.unix_timestamp as u64;
+
+            pending_term.delay_pending = 1;
+            pending_term.delay_effective_at = current_time.checked_add(pending_term.min_delay).ok_or(ArithmeticError)?;
+            pending_term.pending_min_delay = min_delay;
+        }
+
+        pending_term.serialize(&mut &mut pending_term_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn accrue_referral<'a>(
+        payer: &AccountInfo<'a>,
+        referral_pda: &AccountInfo<'a>,
+        referrer: [u8;32],
+        mint: [u8;32],
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        let (referral_address, bump) = Pubkey::find_program_address(&[b"referral", &referrer, &mint], program_id);
+
+        if referral_pda.key != &referral_address {return Err(InvalidReferral.into());}
+
+        let mut referral: Referral;
+
+        if referral_pda.owner == program_id {
+
+            referral = Referral::try_from_slice(&referral_pda.data.borrow())?;
+
+        }else{
+
+            let rent: Rent = Rent::default();
+            let rent_amount: u64 = rent.minimum_balance(82);
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    &referral_address,
+                    rent_amount,
+                    82,
+                    program_id,
+                ),
+                &[payer.clone(), referral_pda.clone()],
+                &[&[b"referral", &referrer, &mint, &[bump]]],
+            )?;
+
+            referral = Referral {
+                initialized: 9,
+                referrer,
+                mint,
+                accrued: 0,
+                withdrawn: 0,
+                bump,
+            };
+        }
+
+        referral.accrued = referral.accrued.checked_add(amount).ok_or(ArithmeticError)?;
+
+        referral.serialize(&mut &mut referral_pda.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    //the attester's signature has to be verified by an ed25519 program instruction right before this one
+    fn check_attestation(
+        raffle: &Raffle,
+        participant: &Pubkey,
+        expiry: u64,
+        current_time: u64,
+        instructions_sysvar: &AccountInfo,
+    ) -> ProgramResult {
+
+        if expiry < current_time {return Err(AttestationExpired.into());}
+
+        let current_index: u16 = load_current_index_checked(instructions_sysvar)?;
+        if current_index == 0 {return Err(InvalidAttestation.into());}
+
+        let ed25519_ix: Instruction = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+        if ed25519_ix.program_id != ed25519_program::ID {return Err(InvalidAttestation.into());}
+
+        let message: Vec<u8> = attestation::message(raffle.raffle_no, &participant.to_bytes(), expiry);
+
+        if !attestation::verify_ed25519_data(&ed25519_ix.data, &raffle.attester, &message) {return Err(InvalidAttestation.into());}
+
+        Ok(())
+    }
+
+    fn check_requirement_balance(
+        raffle: &Raffle, owner: &Pubkey, requirement_ata: &AccountInfo
+    ) -> ProgramResult {
+
+        if requirement_ata.owner != &spl_token::id() && requirement_ata.owner != &spl_token_2022::id() {return Err(RequirementNotMet.into());}
+
+        let ata_unpacked: Account = Account::unpack_from_slice(&requirement_ata.data.borrow())?;
+
+        if ata_unpacked.mint.to_bytes() != raffle.requirement_mint {return Err(RequirementNotMet.into());}
+        if &ata_unpacked.owner != owner {return Err(RequirementNotMet.into());}
+        if ata_unpacked.amount < raffle.requirement_amount_token {return Err(RequirementNotMet.into());}
+
+        Ok(())
+    }
+
+    fn check_collection_nft(
+        participant: &AccountInfo,
+        nft_mint: &AccountInfo,
+        participant_nft_ata: &AccountInfo,
+        nft_metadata: &AccountInfo,
+        collection: [u8;32],
+    ) -> ProgramResult {
+
+        if nft_metadata.owner != &nft::METADATA_PROGRAM_ID {return Err(InvalidCollection.into());}
+        if nft_metadata.key != &nft::metadata_address(nft_mint.key) {return Err(InvalidCollection.into());}
+
+        let (metadata_mint, verified_collection) = nft::verified_collection(&nft_metadata.data.borrow())?;
+
+        if metadata_mint != nft_mint.key.to_bytes() {return Err(InvalidCollection.into());}
+        if verified_collection != Some(collection) {return Err(InvalidCollection.into());}
+
+        if nft_mint.owner != &spl_token::id() && nft_mint.owner != &spl_token_2022::id() {return Err(InvalidCollection.into());}
+        if participant_nft_ata.owner != &spl_token::id() && participant_nft_ata.owner != &spl_token_2022::id() {return Err(InvalidCollection.into());}
+
+        //token-2022 mints and accounts may carry extensions after the base layout
+        let mint_data = nft_mint.data.borrow();
+        let mint_unpacked = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        if mint_unpacked.base.decimals != 0 || mint_unpacked.base.supply != 1 {return Err(InvalidCollection.into());}
+
+        let ata_data = participant_nft_ata.data.borrow();
+        let ata_unpacked = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&ata_data)?;
+        if &ata_unpacked.base.mint != nft_mint.key {return Err(InvalidCollection.into());}
+        if &ata_unpacked.base.owner != participant.key {return Err(InvalidCollection.into());}
+        if ata_unpacked.base.amount < 1 {return Err(InvalidCollection.into());}
+
+        Ok(())
+    }
+
+    fn record_nft_entry<'a>(
+        payer: &AccountInfo<'a>,
+        nft_entry: &AccountInfo<'a>,
+        raffle_no: u64,
+        nft_mint: &Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+
+        let (nft_entry_address, bump) = Pubkey::find_program_address(&[b"nft", &raffle_no.to_le_bytes(), &nft_mint.to_bytes()], program_id);
 
-    terms.serialize(&mut &mut term_account.data.borrow_mut()[..])?;
+        if nft_entry.key != &nft_entry_address {return Err(InvalidCollection.into());}
+        if nft_entry.owner == program_id {return Err(NftAlreadyUsed.into());}
 
-    Ok(())
-}
+        let rent: Rent = Rent::default();
+        let rent_amount: u64 = rent.minimum_balance(42);
 
-    fn collect_fee(
-    accounts: &[AccountInfo],
-    program_id: &Pubkey,
-    ) -> ProgramResult {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                &nft_entry_address,
+                rent_amount,
+                42,
+                program_id,
+            ),
+            &[payer.clone(), nft_entry.clone()],
+            &[&[b"nft", &raffle_no.to_le_bytes(), &nft_mint.to_bytes(), &[bump]]],
+        )?;
 
-    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+        let entry: NftEntry = NftEntry {
+            initialized: 12,
+            raffle_no,
+            mint: nft_mint.to_bytes(),
+            bump,
+        };
 
-    let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let fee_collector: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        entry.serialize(&mut &mut nft_entry.data.borrow_mut()[..])?;
 
-    if config_account.owner != program_id {
-        return Err(InvalidConfig.into());
+        Ok(())
     }
 
+    fn count_entry<'a>(
+        payer: &AccountInfo<'a>,
+        entry_counter: &AccountInfo<'a>,
+        raffle_no: u64,
+        max_entries: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
 
-    let config: Config = Config::try_from_slice(&config_account.data.borrow())?;
-
-    Self::check_authority(authority.key, config)?;
+        let (entry_counter_address, bump) = Pubkey::find_program_address(&[b"entries", &raffle_no.to_le_bytes(), &payer.key.to_bytes()], program_id);
 
-    if !authority.is_signer {
-        return Err(NotSignerAuth.into());
-    }
+        if entry_counter.key != &entry_counter_address {return Err(InvalidEntryCounter.into());}
 
+        let mut counter: EntryCounter;
 
-    let value: u64 = **fee_collector.lamports.borrow();
+        if entry_counter.owner == program_id {
 
-    let rent: Rent = Rent::default();
-    let rent_amount: u64 = rent.minimum_balance(1);
+            counter = EntryCounter::try_from_slice(&entry_counter.data.borrow())?;
 
-    let collected_fee: u64 = value.checked_sub(rent_amount).ok_or(ArithmeticError)?;
+            if counter.initialized != 11 {return Err(InvalidEntryCounter.into());}
 
-    **fee_collector.try_borrow_mut_lamports()? -= collected_fee;
-    **authority.try_borrow_mut_lamports()? += collected_fee;
-    
-    Ok(())
-   }
-    
-   fn add_solana_to_the_reward_pool(
-        accounts: &[AccountInfo],program_id: &Pubkey,amount:Rewards
-   ) -> ProgramResult{
+        }else{
 
-    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+            let rent: Rent = Rent::default();
+            let rent_amount: u64 = rent.minimum_balance(50);
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    &entry_counter_address,
+                    rent_amount,
+                    50,
+                    program_id,
+                ),
+                &[payer.clone(), entry_counter.clone()],
+                &[&[b"entries", &raffle_no.to_le_bytes(), &payer.key.to_bytes(), &[bump]]],
+            )?;
+
+            counter = EntryCounter {
+                initialized: 11,
+                raffle_no,
+                wallet: payer.key.to_bytes(),
+                entries: 0,
+                bump,
+            };
+        }
 
-    let payer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        counter.entries = counter.entries.checked_add(1).ok_or(ArithmeticError)?;
 
-    if raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
+        if counter.entries > max_entries {return Err(EntryLimitReached.into());}
 
-    let mut raffle: Raffle = Raffle::try_from_slice(&raffle_account.data.borrow())?;
+        counter.serialize(&mut &mut entry_counter.data.borrow_mut()[..])?;
 
-    if raffle.raffle_state != 1 {return Err(InvalidRaffleState.into());}
+        Ok(())
+    }
 
-    let total_rewards = amount.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+    fn accrue_fee<'a>(
+        payer: &AccountInfo<'a>,
+        fee_ledger_account: &AccountInfo<'a>,
+        mint: [u8;32],
+        collector_balance: u64,
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
 
-    invoke(&system_instruction::transfer(
-        payer.key,
-        raffle_account.key, 
-        total_rewards), 
-        &[payer.clone(),raffle_account.clone()])?;
+        let (fee_ledger_address, bump) = Pubkey::find_program_address(&[b"fee_ledger", &mint], program_id);
 
-    if raffle.rewards.len() != amount.rewards.len() {return Err(ArithmeticError.into());}
+        if fee_ledger_account.key != &fee_ledger_address {return Err(InvalidFeeLedger.into());}
 
+        let mut fee_ledger: FeeLedger;
 
-    for i in 0..amount.rewards.len() {
+        if fee_ledger_account.owner == program_id {
 
-        raffle.rewards[i] = raffle.rewards[i].checked_add(amount.rewards[i]).ok_or(ArithmeticError)?;
+            fee_ledger = Self::get_fee_ledger(fee_ledger_account, mint, program_id)?;
 
-    }
+        }else{
 
+            let rent: Rent = Rent::default();
+            let rent_amount: u64 = rent.minimum_balance(50);
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    &fee_ledger_address,
+                    rent_amount,
+                    50,
+                    program_id,
+                ),
+                &[payer.clone(), fee_ledger_account.clone()],
+                &[&[b"fee_ledger", &mint, &[bump]]],
+            )?;
+
+            //fees collected before the ledger existed are already sitting in the collector
+            fee_ledger = FeeLedger {
+                initialized: 10,
+                mint,
+                accrued: collector_balance,
+                withdrawn: 0,
+                bump,
+            };
+        }
 
-    raffle.serialize(&mut &mut raffle_account.data.borrow_mut()[..])?;
+        fee_ledger.accrued = fee_ledger.accrued.checked_add(amount).ok_or(ArithmeticError)?;
 
-    Ok(())
-}
+        fee_ledger.serialize(&mut &mut fee_ledger_account.data.borrow_mut()[..])?;
 
-   fn add_tokens_to_the_reward_pool(
-    accounts: &[AccountInfo],program_id: &Pubkey,amount:Rewards
-   ) -> ProgramResult{
+        Ok(())
+    }
 
-let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+    fn get_fee_ledger(
+        fee_ledger_account: &AccountInfo, mint: [u8;32], program_id: &Pubkey
+    ) -> Result<FeeLedger, ProgramError> {
 
-let payer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-let payer_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-let raffle_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-let raffle_ata: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-let reward_token_mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        if fee_ledger_account.owner != program_id {return Err(InvalidFeeLedger.into());}
 
-if raffle_account.owner != program_id {return Err(InvalidRaffle.into());}
+        let fee_ledger: FeeLedger = FeeLedger::try_from_slice(&fee_ledger_account.data.borrow())?;
 
-let mut raffle: Raffle = Raffle::try_from_slice(&raffle_account.data.borrow())?;
+        if fee_ledger.initialized != 10 {return Err(InvalidFeeLedger.into());}
+        if fee_ledger.mint != mint {return Err(InvalidFeeLedger.into());}
 
-if raffle.raffle_state != 1 {return Err(InvalidRaffleState.into());}
+        Ok(fee_ledger)
+    }
 
-let total_rewards = amount.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+    fn withdraw_fee(
+        fee_ledger: &mut FeeLedger, amount: u64
+    ) -> ProgramResult {
 
-Self::transfer_tokens_to_raffle_pda(reward_token_mint, raffle_ata, payer, payer_ata, token_program, 
-    raffle.reward_decimals, total_rewards)?;
+        let available: u64 = fee_ledger.accrued.checked_sub(fee_ledger.withdrawn).ok_or(ArithmeticError)?;
 
-if raffle.rewards.len() != amount.rewards.len() {return Err(ArithmeticError.into());}
+        if amount == 0 || amount > available {return Err(InsufficientFees.into());}
 
+        fee_ledger.withdrawn = fee_ledger.withdrawn.checked_add(amount).ok_or(ArithmeticError)?;
 
-for i in 0..amount.rewards.len() {
+        Ok(())
+    }
 
-    raffle.rewards[i] = raffle.rewards[i].checked_add(amount.rewards[i]).ok_or(ArithmeticError)?;
+    fn resize_account<'a>(
+        account: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        data_len: usize,
+    ) -> ProgramResult {
 
-}
+        let rent: Rent = Rent::default();
+        let rent_amount: u64 = rent.minimum_balance(data_len);
+        let current_lamports: u64 = account.lamports();
 
+        if rent_amount > current_lamports {
+            invoke(&system_instruction::transfer(
+                payer.key,
+                account.key,
+                rent_amount - current_lamports),
+                &[payer.clone(), account.clone()])?;
+        }
 
-raffle.serialize(&mut &mut raffle_account.data.borrow_mut()[..])?;
+        account.realloc(data_len, false)?;
 
-Ok(())
-}
+        Ok(())
+    }
 
 
     fn check_authority(
@@ -1313,7 +3833,7 @@ Ok(())
 
         msg!("init_participant_pda");
         let rent: Rent = Rent::default();
-        let rent_amount: u64 = rent.minimum_balance(58);
+        let rent_amount: u64 = rent.minimum_balance(107);
 
         if multiple_participation_allowed != 1 {
 
@@ -1336,7 +3856,7 @@ Ok(())
                     participant.key,
                      &participant_pda_address,
                      rent_amount,
-                     58,
+                     107,
                      program_id,
                  ),
                  &[participant.clone(), participant_pda.clone()],
@@ -1368,7 +3888,7 @@ Ok(())
                     participant.key,
                      &participant_pda_address,
                      rent_amount,
-                     58,
+                     107,
                      program_id,
                  ),
                  &[participant.clone(), participant_pda.clone()],
@@ -1391,6 +3911,10 @@ Ok(())
             entitled: 0,
             prize_claimed: 0,
             index_in_winners: 0,
+            fee_paid: 0,
+            fee_currency: 0,
+            referrer: [0; 32],
+            referral_share: 0,
         };
 
         {
@@ -1472,6 +3996,7 @@ Ok(())
         program_id: &Pubkey,
         fee_type: &RewardFeeType,
         fee_type_pda: &Pubkey,
+        participation_fee: u64,
     )-> ProgramResult{
 
         let (fee_type_pda_address, _bump) = 
@@ -1486,10 +4011,98 @@ Ok(())
                 return Err(InvalidRewardType.into());
             }
 
+            if fee_type.enabled != 1 {return Err(FeeTypeDisabled.into());}
+            if participation_fee < fee_type.min_participation_fee {return Err(InvalidParticipationFee.into());}
+            if fee_type.max_participation_fee != 0 && participation_fee > fee_type.max_participation_fee {
+                return Err(InvalidParticipationFee.into());
+            }
+
+
+        Ok(())
+    }
+
+    //every price a schedule can reach has to respect the fee type limits
+    fn check_price_schedule(
+        program_id: &Pubkey,
+        fee_type: &RewardFeeType,
+        fee_type_pda: &Pubkey,
+        init_raffle: &InitRaffle,
+    )-> ProgramResult{
+
+        Self::check_participation_fee_type(program_id,fee_type,fee_type_pda,init_raffle.participation_fee)?;
+
+        for price in init_raffle.price_step_prices.iter() {
+            Self::check_participation_fee_type(program_id,fee_type,fee_type_pda,*price)?;
+        }
+
+        if init_raffle.pricing_mode == 2 {
+            Self::check_participation_fee_type(program_id,fee_type,fee_type_pda,init_raffle.end_price)?;
+        }
 
         Ok(())
     }
 
+    //Each accepted currency passes its fee type pda, token currencies also pass the mint, the raffle ata and the token program.
+    //Pool increments and consolations are kept in participation_fee_mint, so they can not be combined with other currencies.
+    fn read_accepted_fees<'a, 'b>(
+        accounts_iter: &mut std::slice::Iter<'b, AccountInfo<'a>>,
+        init_raffle: &InitRaffle,
+        terms: &Term,
+        fee_type: &RewardFeeType,
+        program_id: &Pubkey,
+    ) -> Result<(Vec<AcceptedFee>, Vec<[&'b AccountInfo<'a>; 3]>), ProgramError> {
+
+        let mut accepted_fees: Vec<AcceptedFee> = Vec::new();
+        let mut token_accounts: Vec<[&'b AccountInfo<'a>; 3]> = Vec::new();
+
+        if init_raffle.accepted_fee_types.is_empty() && init_raffle.accepted_fee_prices.is_empty() {return Ok((accepted_fees, token_accounts));}
+
+        if init_raffle.accepted_fee_types.len() != init_raffle.accepted_fee_prices.len() {return Err(InvalidAcceptedFees.into());}
+        if init_raffle.accepted_fee_types.len() > 4 {return Err(InvalidAcceptedFees.into());}
+        if init_raffle.is_increasing_pool == 1 || init_raffle.consolation_type != 0 {return Err(InvalidAcceptedFees.into());}
+
+        for (fee_type_no, price) in init_raffle.accepted_fee_types.iter().zip(init_raffle.accepted_fee_prices.iter()) {
+
+            let accepted_type_pda: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+
+            if accepted_type_pda.owner != program_id {return Err(InvalidFeeType.into());}
+
+            let accepted_type: RewardFeeType = Self::get_reward_fee_type(accepted_type_pda)?;
+
+            if accepted_type.initialized != 3 {return Err(InvalidFeeType.into());}
+            if accepted_type.no != *fee_type_no {return Err(InvalidFeeType.into());}
+
+            Self::check_participation_fee_type(program_id,&accepted_type,accepted_type_pda.key,*price)?;
+
+            if accepted_type.mint == fee_type.mint {return Err(InvalidAcceptedFees.into());}
+            if accepted_fees.iter().any(|accepted| accepted.mint == accepted_type.mint) {return Err(InvalidAcceptedFees.into());}
+            if init_raffle.requirement_to_participate == 1 && init_raffle.requirement_mint == accepted_type.mint {return Err(InvalidMint.into());}
+
+            if accepted_type.no != 1 {
+
+                let accepted_mint: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+                let raffle_accepted_ata: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+                let accepted_token_program: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+
+                if accepted_mint.key.to_bytes() != accepted_type.mint {return Err(InvalidMint.into());}
+
+                token_accounts.push([accepted_mint, raffle_accepted_ata, accepted_token_program]);
+            }
+
+            accepted_fees.push(AcceptedFee{
+                fee_type: accepted_type.no,
+                mint: accepted_type.mint,
+                decimals: accepted_type.decimals,
+                price: *price,
+                fee_bps: if accepted_type.has_fee_bps == 1 {accepted_type.fee_bps} else {terms.fee_bps},
+                fees_received: 0,
+                fee_collected: 0,
+            });
+        }
+
+        Ok((accepted_fees, token_accounts))
+    }
+
     fn check_participation_reward_type_and_sum(
         program_id: &Pubkey,
         reward_type: &RewardFeeType,
@@ -1520,36 +4133,17 @@ Ok(())
         Ok(())
     }
 
-    fn check_and_write_raffle_data<'a>(
+    //everything about the raffle data that does not depend on when it is created,
+    //init_series runs it on the template so a bad template is refused before the escrow is funded
+    fn check_raffle_data(
         init_raffle:&InitRaffle,
         terms:&Term,
         reward_mint:[u8;32],
         participation_fee_mint:[u8;32],
-        reward_decimals:u8,
-        participation_fee_decimals:u8,
-        raffle_no:u64,
-        raffle_pda:&AccountInfo<'a>,
-        initializer:&AccountInfo<'a>,
-        program_id: &Pubkey
-
     )-> ProgramResult{
 
-
-        let clock: Clock= Clock::get()?;
-        let current_time: u64 = clock.unix_timestamp as u64;
-        let maximum_time_allowed: u64  = current_time.checked_add(terms.expiration_time).ok_or(ArithmeticError)?;
-
-        if init_raffle.raffle_time < current_time{return Err(InvalidRaffleTime.into());}//no raffle in the past allowed
-        if init_raffle.raffle_time > maximum_time_allowed{return Err(InvalidRaffleTime.into());}//no raffle allowed in the far future
         if init_raffle.winner_count > terms.maximum_winner_count {return Err(InvalidWinnerNumber.into());}
-
-        let n: usize = init_raffle.winner_count as usize;
-        let winners: Vec<u64> = vec![0; n];
-
-        if winners.len() != init_raffle.rewards.len() {return Err(InvalidWinnerNumber.into());}
-
-        let (raffle_account_address, bump) = 
-        Pubkey::find_program_address(&[b"raffle", &raffle_no.to_le_bytes()], program_id);
+        if init_raffle.rewards.len() as u64 != init_raffle.winner_count {return Err(InvalidWinnerNumber.into());}
 
         msg!("{:?}",reward_mint);
         msg!("{}",init_raffle.is_increasing_pool);
@@ -1562,6 +4156,50 @@ Ok(())
             if reward_mint != participation_fee_mint { return Err(InvalidMint.into());}
         }
 
+        if init_raffle.referral_bps > BPS_DENOMINATOR {return Err(InvalidReferral.into());}
+
+        if !init_raffle.payees.is_empty() {
+            if init_raffle.payees.len() > 10 {return Err(InvalidRevenueSplit.into());}
+            if init_raffle.payees.len() != init_raffle.payee_shares_bps.len() {return Err(InvalidRevenueSplit.into());}
+            if init_raffle.payees.contains(&[0; 32]) {return Err(InvalidRevenueSplit.into());}
+            if init_raffle.payee_shares_bps.contains(&0) {return Err(InvalidRevenueSplit.into());}
+            let total_bps: u64 = init_raffle.payee_shares_bps.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+            if total_bps != BPS_DENOMINATOR {return Err(InvalidRevenueSplit.into());}
+        }else if !init_raffle.payee_shares_bps.is_empty() {
+            return Err(InvalidRevenueSplit.into());
+        }
+
+        if init_raffle.pool_bps != 0 {
+            if init_raffle.is_increasing_pool != 1 {return Err(InvalidPoolSplit.into());}
+            if !init_raffle.transfer_fee_to_pool.is_empty() {return Err(InvalidPoolSplit.into());}
+            if init_raffle.pool_weights_bps.len() != init_raffle.rewards.len() {return Err(InvalidPoolSplit.into());}
+            let total_weight_bps: u64 = init_raffle.pool_weights_bps.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+            if total_weight_bps != BPS_DENOMINATOR {return Err(InvalidPoolSplit.into());}
+            if init_raffle.pool_bps.checked_add(init_raffle.referral_bps).ok_or(ArithmeticError)? > BPS_DENOMINATOR {return Err(InvalidPoolSplit.into());}
+        }else if !init_raffle.pool_weights_bps.is_empty() {
+            return Err(InvalidPoolSplit.into());
+        }
+
+        if init_raffle.pricing_mode > 2 {return Err(InvalidPriceSchedule.into());}
+        if init_raffle.pricing_mode == 1 {
+            if init_raffle.price_step_times.is_empty() || init_raffle.price_step_times.len() > 16 {return Err(InvalidPriceSchedule.into());}
+            if init_raffle.price_step_times.len() != init_raffle.price_step_prices.len() {return Err(InvalidPriceSchedule.into());}
+            if init_raffle.price_step_times.windows(2).any(|pair| pair[0] >= pair[1]) {return Err(InvalidPriceSchedule.into());}
+        }else if !init_raffle.price_step_times.is_empty() || !init_raffle.price_step_prices.is_empty() {
+            return Err(InvalidPriceSchedule.into());
+        }
+        if init_raffle.pricing_mode != 2 && init_raffle.end_price != 0 {return Err(InvalidPriceSchedule.into());}
+
+        //pool increments and the referral share are both paid out of a single participation fee,
+        //with a price schedule they have to fit into the lowest price
+        if init_raffle.is_increasing_pool == 1 {
+            let mut lowest_price: u64 = init_raffle.price_step_prices.iter().fold(init_raffle.participation_fee, |acc, &x| acc.min(x));
+            if init_raffle.pricing_mode == 2 {lowest_price = lowest_price.min(init_raffle.end_price);}
+            let pool_per_entry: u64 = init_raffle.transfer_fee_to_pool.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+            let referral_share: u64 = bps_share(lowest_price, init_raffle.referral_bps)?;
+            if pool_per_entry.checked_add(referral_share).ok_or(ArithmeticError)? > lowest_price {return Err(InvalidReferral.into());}
+        }
+
 
 
         if init_raffle.requirement_to_participate == 1 {
@@ -1569,17 +4207,74 @@ Ok(())
             if init_raffle.requirement_mint == participation_fee_mint{return Err(InvalidMint.into());}
         }
 
+        if init_raffle.is_unlimited_participant_allowed != 1 && init_raffle.min_participants > init_raffle.max_participants {
+            return Err(InvalidParticipantLimits.into());
+        }
+
+        if !init_raffle.prize_shares_bps.is_empty() {
+            if init_raffle.prize_shares_bps.len() != init_raffle.rewards.len() {return Err(InvalidPrizeShares.into());}
+            if init_raffle.prize_shares_bps.contains(&0) {return Err(InvalidPrizeShares.into());}
+            let total_share_bps: u64 = init_raffle.prize_shares_bps.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+            if total_share_bps != BPS_DENOMINATOR {return Err(InvalidPrizeShares.into());}
+        }
+
+        if init_raffle.consolation_type > 2 {return Err(InvalidConsolation.into());}
+        if init_raffle.consolation_type == 0 && (init_raffle.consolation_amount != 0 || init_raffle.consolation_funding != 0) {return Err(InvalidConsolation.into());}
+        if init_raffle.consolation_type != 0 && init_raffle.consolation_amount == 0 {return Err(InvalidConsolation.into());}
+        if init_raffle.consolation_type == 2 && init_raffle.consolation_amount > BPS_DENOMINATOR {return Err(InvalidConsolation.into());}
+
+        if init_raffle.rollover > 1 {return Err(InvalidRollover.into());}
+        if init_raffle.rollover == 0 && init_raffle.claim_period != 0 {return Err(InvalidRollover.into());}
+
+        if init_raffle.requirement_to_participate > 3 {return Err(InvalidRequirement.into());}
+        if init_raffle.requirement_to_participate == 2 && init_raffle.requirement_mint == [0; 32] {return Err(InvalidCollection.into());}
+        if init_raffle.requirement_to_participate == 3 {
+            if init_raffle.requirement_mint == [0; 32] {return Err(InvalidRequirement.into());}
+            if init_raffle.requirement_amount_token == 0 {return Err(InvalidRequirement.into());}
+        }
+
+        Ok(())
+    }
+
+    fn check_and_write_raffle_data<'a>(
+        init_raffle:&InitRaffle,
+        terms:&Term,
+        setup:RaffleSetup,
+        raffle_pda:&AccountInfo<'a>,
+        payer:&AccountInfo<'a>,
+        initializer:&Pubkey,
+        program_id: &Pubkey
+
+    )-> ProgramResult{
+
+        let RaffleSetup {raffle_no, fee_bps, reward_mint, reward_decimals, participation_fee_mint, participation_fee_decimals, accepted_fees} = setup;
+
 
+        let clock: Clock= Clock::get()?;
+        let current_time: u64 = clock.unix_timestamp as u64;
+        let maximum_time_allowed: u64  = current_time.checked_add(terms.expiration_time).ok_or(ArithmeticError)?;
+
+        if init_raffle.raffle_time < current_time{return Err(InvalidRaffleTime.into());}//no raffle in the past allowed
+        if init_raffle.raffle_time > maximum_time_allowed{return Err(InvalidRaffleTime.into());}//no raffle allowed in the far future
+        if init_raffle.start_time != 0 && init_raffle.start_time >= init_raffle.raffle_time {return Err(InvalidRaffleTime.into());}//raffle_time already bounds it by expiration_time
+
+        Self::check_raffle_data(init_raffle, terms, reward_mint, participation_fee_mint)?;
+
+        let n: usize = init_raffle.winner_count as usize;
+        let winners: Vec<u64> = vec![0; n];
+
+        let (raffle_account_address, bump) = 
+        Pubkey::find_program_address(&[b"raffle", &raffle_no.to_le_bytes()], program_id);
 
         let data: Raffle = Raffle{
             raffle_state: 1,
             reward_decimals,
-            initializer: initializer.key.to_bytes(),
+            initializer: initializer.to_bytes(),
             reward_mint,
             raffle_name: init_raffle.raffle_name,
             raffle_no,
             current_number_of_participants: 0,
-            participants_required:init_raffle.participants_required,
+            max_participants:init_raffle.max_participants,
             participation_fee: init_raffle.participation_fee,
             raffle_time:init_raffle.raffle_time,
             is_unlimited_participant_allowed: init_raffle.is_unlimited_participant_allowed,
@@ -1600,12 +4295,50 @@ Ok(())
             is_increasing_pool: init_raffle.is_increasing_pool,
             transfer_fee_to_pool: init_raffle.transfer_fee_to_pool.clone(),
             fee_collected: 0,
+            fee_bps,
+            expiration_time: terms.expiration_time,
+            maximum_winner_count: terms.maximum_winner_count,
+            created_at: current_time,
+            referral_bps: init_raffle.referral_bps,
+            payees: init_raffle.payees.clone(),
+            payee_shares_bps: init_raffle.payee_shares_bps.clone(),
+            allowlist_root: init_raffle.allowlist_root,
+            one_entry_per_nft: init_raffle.one_entry_per_nft,
+            recheck_requirement_at_claim: init_raffle.recheck_requirement_at_claim,
+            max_entries_per_wallet: init_raffle.max_entries_per_wallet,
+            min_participants: init_raffle.min_participants,
+            refund_below_min: init_raffle.refund_below_min,
+            start_time: init_raffle.start_time,
+            extension_count: 0,
+            series_id: 0,
+            round_no: 0,
+            rollover: init_raffle.rollover,
+            claim_period: init_raffle.claim_period,
+            published_at: 0,
+            rewards_claimed: 0,
+            rollover_amount: 0,
+            rolled_over: 0,
+            pool_bps: init_raffle.pool_bps,
+            pool_weights_bps: init_raffle.pool_weights_bps.clone(),
+            fees_received: 0,
+            fees_to_pool: 0,
+            prize_shares_bps: init_raffle.prize_shares_bps.clone(),
+            consolation_type: init_raffle.consolation_type,
+            consolation_amount: init_raffle.consolation_amount,
+            consolation_escrow: init_raffle.consolation_funding,
+            consolation_reserved: 0,
+            pricing_mode: init_raffle.pricing_mode,
+            price_step_times: init_raffle.price_step_times.clone(),
+            price_step_prices: init_raffle.price_step_prices.clone(),
+            end_price: init_raffle.end_price,
+            attester: init_raffle.attester,
+            accepted_fees,
         };
 
         let mut serialized_data: Vec<u8> = Vec::new();
         data.serialize(&mut serialized_data)?;
 
-        Self::create_raffle_pda(initializer, raffle_pda, program_id, &raffle_account_address,&raffle_no, serialized_data.len(),bump)?;
+        Self::create_raffle_pda(payer, raffle_pda, program_id, &raffle_account_address,&raffle_no, serialized_data.len(),bump)?;
 
         data.serialize(&mut &mut raffle_pda.data.borrow_mut()[..])?;
 
@@ -1627,7 +4360,7 @@ Ok(())
 
         if counter.initialized != 1 {return Err(InvalidCounter.into());}
 
-        if terms.initialized != 2 {return Err(InvalidTerms.into());}
+        if terms.initialized != 14 {return Err(InvalidTerms.into());}
 
 
         if counter_account.owner != program_id{return Err(InvalidCounter.into());}
@@ -1696,14 +4429,87 @@ Ok(())
         Ok(())
     }
     
+    //participant accounts keep the size they were created with, fields added later read as zero
+    fn get_participant(
+        participant_pda: &AccountInfo
+    ) -> Result<Participant, ProgramError> {
+
+        let mut data: Vec<u8> = participant_pda.data.borrow().to_vec();
+
+        if data.len() != 58 && data.len() != 107 {return Err(InvalidParticipantPDA.into());}
+
+        data.resize(107, 0);
+
+        Ok(Participant::try_from_slice(&data)?)
+    }
+
+    fn write_participant(
+        participant: &Participant, participant_pda: &AccountInfo
+    ) -> ProgramResult {
+
+        let mut serialized: Vec<u8> = Vec::new();
+        participant.serialize(&mut serialized)?;
+
+        //fields an older account does not have are never set on it, so they are dropped
+        let mut data = participant_pda.data.borrow_mut();
+        let data_len: usize = data.len();
+        data.copy_from_slice(&serialized[..data_len]);
+
+        Ok(())
+    }
+
+    fn consolation_for(
+        raffle: &Raffle, fee_paid: u64
+    ) -> Result<u64, ProgramError> {
+
+        match raffle.consolation_type {
+            1 => Ok(raffle.consolation_amount),
+            2 => bps_share(fee_paid, raffle.consolation_amount),
+            _ => Ok(0),
+        }
+    }
+
+    //fee type no, mint and decimals of the currency a participant paid in
+    fn fee_currency_of(
+        raffle: &Raffle, fee_currency: u8
+    ) -> Result<(u64, [u8;32], u8), ProgramError> {
+
+        if fee_currency == 0 {
+            return Ok((raffle.participation_fee_type, raffle.participation_fee_mint, raffle.participation_fee_decimals));
+        }
+
+        let accepted: &AcceptedFee = raffle.accepted_fees.get(fee_currency as usize - 1).ok_or(InvalidFeeType)?;
+
+        Ok((accepted.fee_type, accepted.mint, accepted.decimals))
+    }
+
+    //Turns prize shares into amounts once the winners are known. Slots left without a winner get nothing,
+    //their shares are spread over the drawn slots so the whole pool is paid out.
+    fn freeze_prize_shares(
+        raffle: &mut Raffle
+    ) -> ProgramResult {
+
+        if raffle.prize_shares_bps.is_empty() {return Ok(());}
+
+        let pool: u64 = raffle.rewards.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ArithmeticError))?;
+        let drawn: usize = raffle.current_winner_count as usize;
+
+        let mut amounts: Vec<u64> = split_by_weights(pool, &raffle.prize_shares_bps[..drawn])?;
+        amounts.resize(raffle.rewards.len(), 0);
+
+        raffle.rewards = amounts;
+
+        Ok(())
+    }
+
     fn check_mint_and_owner(
         mint: &Pubkey,owner: &Pubkey,ata:&AccountInfo
     ) -> ProgramResult {
 
         let ata_unpacked: spl_token::state::Account = Account::unpack_from_slice(&ata.data.borrow())?;
     
-        if mint != &ata_unpacked.mint {panic!()}
-        if owner != &ata_unpacked.owner {panic!()}
+        if mint != &ata_unpacked.mint {return Err(InvalidMint.into());}
+        if owner != &ata_unpacked.owner {return Err(InvalidTokenAccount.into());}
     
         Ok(())
 
@@ -1715,9 +4521,9 @@ Ok(())
 
         let ata_unpacked: spl_token::state::Account = Account::unpack_from_slice(&ata.data.borrow())?;
 
-        if mint != &ata_unpacked.mint {panic!()}
-        if owner != &ata_unpacked.owner {panic!()}
-        if amount > ata_unpacked.amount{panic!()}
+        if mint != &ata_unpacked.mint {return Err(InvalidMint.into());}
+        if owner != &ata_unpacked.owner {return Err(InvalidTokenAccount.into());}
+        if amount > ata_unpacked.amount {return Err(InvalidTokenAccount.into());}
 
         Ok(())
 
@@ -1791,6 +4597,36 @@ Ok(())
         Ok(())
     }
     
+    fn transfer_tokens_from_series<'a>(
+        mint:&AccountInfo<'a>,
+        series_ata:&AccountInfo<'a>,
+        series_account:&AccountInfo<'a>,
+        destination_ata:&AccountInfo<'a>,
+        token_program:&AccountInfo<'a>,
+        amount:u64,
+        series:&RaffleSeries,
+
+    )-> ProgramResult{
+
+        let decimals: u8 = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint.data.borrow())?.base.decimals;
+
+        let transfer_token_ix = spl_token::instruction::transfer_checked(
+            token_program.key,
+            series_ata.key, 
+            mint.key, 
+            destination_ata.key, 
+            series_account.key, 
+            &[],amount,decimals)?;
+
+        invoke_signed(
+        &transfer_token_ix, 
+        &[token_program.clone(),series_ata.clone(),mint.clone(),destination_ata.clone(),series_account.clone()],
+        &[&[b"series", &series.creator, &series.series_id.to_le_bytes(), &[series.bump]]],
+        )?;
+
+        Ok(())
+    }
+    
     fn transfer_tokens_to_raffle_pda<'a>(
         mint:&AccountInfo<'a>,
         raffle_ata:&AccountInfo<'a>,
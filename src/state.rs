@@ -6,7 +6,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct Raffle{
-    pub raffle_state:u8,
+    pub raffle_state:u8, //1 open, 2 drawing, 3 finished, 4 refunding
     pub is_unlimited_participant_allowed:u8,
     pub multiple_participation_allowed:u8,
     pub initializer:[u8;32],
@@ -14,13 +14,13 @@ pub struct Raffle{
     pub raffle_name:[u8;32],
     pub raffle_no:u64,
     pub current_number_of_participants:u64,
-    pub participants_required:u64,
+    pub max_participants:u64,
     pub participation_fee:u64,
     pub participation_fee_mint:[u8;32],  //Type of the fee
     pub participation_fee_type: u64,
     pub rewards:Vec<u64>,  //write the number of tokens to distribute
     pub winners:Vec<u64>,
-    pub requirement_to_participate:u8, //0 no req, 1 token
+    pub requirement_to_participate:u8, //0 no req, 1 escrowed token, 2 nft collection, 3 token balance
     pub requirement_amount_token:u64,  //
     pub requirement_mint:[u8;32],  //
     pub required_token_decimals:u8,
@@ -33,15 +33,109 @@ pub struct Raffle{
     pub current_winner_count: u64,
     pub number_of_entitled_winners: u64,
     pub fee_collected:u8,
+    pub fee_bps:u64,              //term values in force at init_raffle
+    pub expiration_time:u64,
+    pub maximum_winner_count:u64,
+    pub created_at:u64,
+    pub referral_bps:u64, //share of each participation fee paid to the referrer
+    pub payees:Vec<[u8;32]>, //empty, creator share goes to the initializer
+    pub payee_shares_bps:Vec<u64>,
+    pub allowlist_root:[u8;32], //zero, anyone can join
+    pub one_entry_per_nft:u8, //requirement 2, each nft can be used for a single entry
+    pub recheck_requirement_at_claim:u8, //requirement 3, winners must still hold the balance to claim
+    pub max_entries_per_wallet:u64, //0 no cap
+    pub min_participants:u64,
+    pub refund_below_min:u8, //1 refund participants when min is not reached, 0 abort and keep the fees
+    pub start_time:u64, //entries open at this time, 0 open at creation
+    pub extension_count:u8,
+    pub series_id:u64, //0 not part of a series
+    pub round_no:u64,
+    pub rollover:u8, //1 an empty pool and expired prizes go to the next raffle of the creator instead of back to them
+    pub claim_period:u64, //rollover only, prizes can be claimed until published_at plus claim_period, 0 no expiry
+    pub published_at:u64, //when the raffle reached state 3
+    pub rewards_claimed:u64,
+    pub rollover_amount:u64, //pool kept from an aborted draw, waiting for rollover_pool
+    pub rolled_over:u8,
+    pub pool_bps:u64, //increasing pool, share of each participation fee added to the pool, 0 use transfer_fee_to_pool
+    pub pool_weights_bps:Vec<u64>, //pool_bps only, split of the pool share across winner slots
+    pub fees_received:u64, //participation fees paid into the raffle, referral shares excluded
+    pub fees_to_pool:u64, //part of fees_received added to rewards
+    pub prize_shares_bps:Vec<u64>, //empty rewards are absolute, otherwise the pool is split by these shares when the draw completes
+    pub consolation_type:u8, //0 none, 1 fixed amount, 2 share of the fee paid, both paid in the participation fee mint
+    pub consolation_amount:u64, //type 1 amount per non-winning entry, type 2 basis points of fee_paid
+    pub consolation_escrow:u64, //funded by the initializer at creation, fees cover whatever it does not
+    pub consolation_reserved:u64, //total owed to non-winning entries, winners are released at publish_winner
+    pub pricing_mode:u8, //0 flat participation_fee, 1 price steps, 2 linear from participation_fee to end_price
+    pub price_step_times:Vec<u64>, //mode 1, increasing, participation_fee applies before the first step
+    pub price_step_prices:Vec<u64>,
+    pub end_price:u64, //mode 2, reached at raffle_time, the curve starts at start_time or created_at
+    pub attester:[u8;32], //zero, no attestation needed to join
+    pub accepted_fees:Vec<AcceptedFee>, //currencies accepted besides participation_fee_mint
     pub bump:u8
   }
 
-#[derive(BorshDeserialize, Debug, PartialEq)]
+//raffle layout written before term snapshots, only read by migrate_raffle
+#[derive(BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct LegacyRaffle{
+    pub raffle_state:u8,
+    pub is_unlimited_participant_allowed:u8,
+    pub multiple_participation_allowed:u8,
+    pub initializer:[u8;32],
+    pub reward_mint:[u8;32],
+    pub raffle_name:[u8;32],
+    pub raffle_no:u64,
+    pub current_number_of_participants:u64,
+    pub participants_required:u64,
+    pub participation_fee:u64,
+    pub participation_fee_mint:[u8;32],
+    pub participation_fee_type: u64,
+    pub rewards:Vec<u64>,
+    pub winners:Vec<u64>,
+    pub requirement_to_participate:u8,
+    pub requirement_amount_token:u64,
+    pub requirement_mint:[u8;32],
+    pub required_token_decimals:u8,
+    pub reward_decimals:u8,
+    pub participation_fee_decimals:u8,
+    pub is_increasing_pool:u8,
+    pub transfer_fee_to_pool:Vec<u64>,
+    pub raffle_time:u64,
+    pub winner_count: u64,
+    pub current_winner_count: u64,
+    pub number_of_entitled_winners: u64,
+    pub fee_collected:u8,
+    pub bump:u8
+  }
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AcceptedFee{
+    pub fee_type:u64,
+    pub mint:[u8;32],
+    pub decimals:u8,
+    pub price:u64, //flat, price schedules only apply to participation_fee
+    pub fee_bps:u64, //term values in force at init_raffle for this fee type
+    pub fees_received:u64,
+    pub fee_collected:u8,
+}
+
+//what init_raffle and spawn_series_round resolve from the counter and the fee types before writing a raffle
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaffleSetup{
+    pub raffle_no:u64,
+    pub fee_bps:u64,
+    pub reward_mint:[u8;32],
+    pub reward_decimals:u8,
+    pub participation_fee_mint:[u8;32],
+    pub participation_fee_decimals:u8,
+    pub accepted_fees:Vec<AcceptedFee>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct InitRaffle{
   pub is_unlimited_participant_allowed:u8,
   pub raffle_name:[u8;32],
   pub participation_fee:u64,
-  pub participants_required:u64,
+  pub max_participants:u64,
   pub raffle_time:u64,
   pub multiple_participation_allowed: u8,
   pub participation_fee_type: u64,
@@ -54,6 +148,31 @@ pub struct InitRaffle{
   pub winner_count: u64,
   pub is_increasing_pool:u8,
   pub transfer_fee_to_pool:Vec<u64>,
+  pub referral_bps:u64,
+  pub payees:Vec<[u8;32]>,
+  pub payee_shares_bps:Vec<u64>,
+  pub allowlist_root:[u8;32],
+  pub one_entry_per_nft:u8,
+  pub recheck_requirement_at_claim:u8,
+  pub max_entries_per_wallet:u64,
+  pub min_participants:u64,
+  pub refund_below_min:u8,
+  pub start_time:u64,
+  pub rollover:u8,
+  pub claim_period:u64,
+  pub pool_bps:u64,
+  pub pool_weights_bps:Vec<u64>,
+  pub prize_shares_bps:Vec<u64>,
+  pub consolation_type:u8,
+  pub consolation_amount:u64,
+  pub consolation_funding:u64,
+  pub pricing_mode:u8,
+  pub price_step_times:Vec<u64>,
+  pub price_step_prices:Vec<u64>,
+  pub end_price:u64,
+  pub attester:[u8;32],
+  pub accepted_fee_types:Vec<u64>,
+  pub accepted_fee_prices:Vec<u64>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//48
@@ -63,8 +182,12 @@ pub struct Participant{
     pub raffle_no:u64,
     pub entitled:u8,
     pub prize_claimed:u8,
-    pub index_in_winners:u64
-}//58
+    pub index_in_winners:u64,
+    pub fee_paid:u64, //full price paid, refunded when the raffle does not reach min_participants
+    pub fee_currency:u8, //0 participation_fee_mint, n accepted_fees[n-1]
+    pub referrer:[u8;32], //refund_below_min only, the referral share stays in the raffle until it is published
+    pub referral_share:u64,
+}//107, 58 before refunds, currencies and held referral shares
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//9
 pub struct RaffleCounter{
@@ -79,19 +202,22 @@ pub struct RandomNumber{
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
 pub struct Term{
-  pub initialized:u8,
-  pub fee_percent:u64,
+  pub initialized:u8, //14, 2 when fee_bps still holds the old fee_percent
+  pub fee_bps:u64, //protocol fee in basis points, see fees::split_fee for rounding
   pub expiration_time:u64,
   pub maximum_winner_count:u64,
 }
 
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//9
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//162, 128 before governance, pause and treasury
 pub struct Config{
     pub authority_1:[u8;32],
     pub authority_2:[u8;32],
     pub authority_3:[u8;32],
     pub authority_4:[u8;32],
+    pub governance_enabled:u8, //1 config changes only through proposals
+    pub paused:u8, //1 no new raffles or entries
+    pub treasury:[u8;32], //destination of collected protocol fees
 }
 
 
@@ -101,6 +227,29 @@ pub struct RewardFeeType{
     pub mint:[u8;32],
     pub decimals:u8,
     pub no:u64,
+    pub enabled:u8,
+    pub has_fee_bps:u8,
+    pub fee_bps:u64,
+    pub min_participation_fee:u64,
+    pub max_participation_fee:u64,//0 means no upper bound
+}
+
+//reward types have no fee schedule, InitRewType only sends the fields from before fee schedules
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct RewardType{
+    pub initialized:u8,
+    pub mint:[u8;32],
+    pub decimals:u8,
+    pub no:u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct FeeSchedule{
+    pub enabled:u8,
+    pub has_fee_bps:u8,
+    pub fee_bps:u64,
+    pub min_participation_fee:u64,
+    pub max_participation_fee:u64,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//9
@@ -113,4 +262,164 @@ pub struct Rewards{
 
   pub rewards: Vec<u64>,
 
-}
\ No newline at end of file
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct GovernanceSettings{
+    pub threshold:u8,
+    pub members:Vec<[u8;32]>, //at most 64, approvals are kept as a bitmask
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Governance{
+    pub initialized:u8,
+    pub threshold:u8,
+    pub version:u64, //bumped on every member change, older proposals become stale
+    pub proposal_count:u64,
+    pub members:Vec<[u8;32]>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProposalData{
+    pub action:u8, //2 update term, 3 set governance, 4 set roles, 5 propose authority, 6 set term delay, 7 set treasury
+    pub payload:Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Proposal{
+    pub initialized:u8,
+    pub proposal_no:u64,
+    pub governance_version:u64,
+    pub proposer:[u8;32],
+    pub action:u8,
+    pub approvals:u64, //bit i set when members[i] approved
+    pub executed:u8,
+    pub payload:Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ConfigTreasury{
+    pub treasury:[u8;32],
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Roles{
+    pub initialized:u8,
+    pub drawers:Vec<[u8;32]>,         //choose winners
+    pub treasurers:Vec<[u8;32]>,      //collect fees, close participant pdas
+    pub terms_admins:Vec<[u8;32]>,    //init and update terms
+    pub type_registrars:Vec<[u8;32]>, //init fee and reward types
+    pub pausers:Vec<[u8;32]>,         //pause and unpause the program
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AuthorityProposal{
+    pub slot:u8, //1-4, authority_1..authority_4 in config
+    pub new_authority:[u8;32],
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//66
+pub struct AuthorityRotation{
+    pub initialized:u8,
+    pub slot:u8,
+    pub current_authority:[u8;32], //slot value when proposed, acceptance fails if it changed
+    pub pending_authority:[u8;32], //zero when nothing is pending
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//67
+pub struct PendingTerm{
+    pub initialized:u8,
+    pub min_delay:u64,  //seconds between queueing and applying a change
+    pub term_pending:u8,
+    pub term_effective_at:u64,
+    pub fee_bps:u64,
+    pub expiration_time:u64,
+    pub maximum_winner_count:u64,
+    pub delay_pending:u8, //only decreases of min_delay are queued
+    pub delay_effective_at:u64,
+    pub pending_min_delay:u64,
+    pub term_queued_at:u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct RaffleExtension{
+  pub new_raffle_time:u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RaffleSeries{
+    pub initialized:u8,
+    pub creator:[u8;32],
+    pub series_id:u64,
+    pub round_no:u64, //rounds spawned so far
+    pub current_raffle_no:u64, //raffle of the latest round, 0 before the first one
+    pub active:u8,
+    pub duration:u64, //raffle_time of each round is its spawn time plus duration
+    pub template:InitRaffle, //raffle_time and start_time are ignored
+    pub bump:u8,
+}
+
+#[derive(BorshDeserialize, Debug, PartialEq)]
+pub struct InitSeries{
+  pub series_id:u64,
+  pub duration:u64,
+  pub template:InitRaffle,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct TermDelay{
+  pub min_delay:u64,
+}
+
+#[derive(BorshDeserialize, Debug, PartialEq)]
+pub struct JoinRaffle{
+  pub referrer:[u8;32], //zero when the participant was not referred
+  pub proof:Vec<[u8;32]>, //empty unless the raffle has an allowlist
+  pub allowed_entries:u64, //entry cap committed in the allowlist leaf, 0 no cap
+  pub attestation_expiry:u64, //signed by the attester together with raffle_no and the participant
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//42
+pub struct NftEntry{
+    pub initialized:u8,
+    pub raffle_no:u64,
+    pub mint:[u8;32],
+    pub bump:u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//50
+pub struct EntryCounter{
+    pub initialized:u8,
+    pub raffle_no:u64,
+    pub wallet:[u8;32],
+    pub entries:u64,
+    pub bump:u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//50
+pub struct FeeLedger{
+    pub initialized:u8,
+    pub mint:[u8;32], //system program for SOL
+    pub accrued:u64,
+    pub withdrawn:u64,
+    pub bump:u8,
+}
+
+#[derive(BorshDeserialize, Debug, PartialEq)]
+pub struct FeeWithdrawal{
+    pub amount:u64,
+}
+
+#[derive(BorshDeserialize, Debug, PartialEq)]
+pub struct FeeCurrency{
+    pub fee_currency:u8, //0 participation_fee_mint, n accepted_fees[n-1]
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]//82
+pub struct Referral{
+    pub initialized:u8,
+    pub referrer:[u8;32],
+    pub mint:[u8;32], //system program for SOL
+    pub accrued:u64,
+    pub withdrawn:u64,
+    pub bump:u8,
+}